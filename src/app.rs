@@ -5,7 +5,34 @@ pub fn build_app() -> App<'static, 'static> {
         .version(crate_version!())
         .author(crate_authors!())
         .about(crate_description!())
-        .subcommand(SubCommand::with_name("init").about("initialize centoria for shell"))
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .value_name("PATH")
+                .help("path to configuration file, same as $CENTORIA_CONFIG_PATH")
+                .global(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("error_format")
+                .long("error-format")
+                .value_name("FORMAT")
+                .help("format of error messages printed to stderr")
+                .possible_values(&["text", "json"])
+                .default_value("text")
+                .global(true)
+                .takes_value(true),
+        )
+        .subcommand(
+            SubCommand::with_name("init")
+                .about("initialize centoria for shell")
+                .arg(
+                    Arg::with_name("all")
+                        .short("a")
+                        .long("all")
+                        .help("export all functions without evaluating their conditions"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("add")
                 .about("add a function to centoria")
@@ -32,7 +59,7 @@ pub fn build_app() -> App<'static, 'static> {
                 .arg(
                     Arg::with_name("cwd")
                     .long("cwd")
-                    .help("working directory for running this function, ignored on subcommand")
+                    .help("working directory for running this function, relative to the configuration file")
                     .takes_value(true)
                 )
                 .arg(
@@ -95,6 +122,12 @@ pub fn build_app() -> App<'static, 'static> {
                     .long("dry-run")
                     .help("run command as dry-run")
                 )
+                .arg(
+                    Arg::with_name("interactive")
+                    .short("i")
+                    .long("interactive")
+                    .help("prompt for missing arguments (only on terminal)")
+                )
                 .arg(
                     Arg::with_name("extra")
                         .help("extra arguments of original command")
@@ -110,9 +143,14 @@ pub fn build_app() -> App<'static, 'static> {
                 .arg(
                     Arg::with_name("name")
                         .help("name of the function to display")
-                        .required(true)
+                        .required_unless("placeholders")
                         .index(1),
                 )
+                .arg(
+                    Arg::with_name("placeholders")
+                        .long("placeholders")
+                        .help("list placeholders available in commands instead of a function"),
+                )
                 .arg(
                     Arg::with_name("subcommand")
                         .short("s")
@@ -121,4 +159,28 @@ pub fn build_app() -> App<'static, 'static> {
                         .takes_value(true),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("complete")
+                .about("print completion candidates for the last argument of a function")
+                .arg(
+                    Arg::with_name("name")
+                        .help("name of the function to complete")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("extra")
+                        .help("arguments typed so far, the last one is being completed")
+                        .multiple(true)
+                        .takes_value(true)
+                        .last(true),
+                ),
+        )
+        .subcommand(SubCommand::with_name("check").about("validate all configuration files"))
+        .subcommand(SubCommand::with_name("doctor").about("diagnose centoria environment"))
+        .subcommand(
+            SubCommand::with_name("cache")
+                .about("manage cached results of conditions")
+                .subcommand(SubCommand::with_name("clear").about("clear all cached results")),
+        )
 }
@@ -1,71 +1,267 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::ops::Range;
+use std::path;
 
 use itertools::Itertools;
 use regex::{Captures, Regex};
 
+use crate::error::Error;
+use crate::fmt;
+use crate::interpreter::Quoting;
+use crate::placeholders::{self, Context};
+
 pub struct ArgParser {
     arguments: Option<Vec<Argument>>, // parser cache
-    descriptions: Option<Vec<String>>,
+    context: Option<Context>,         // values for built-in placeholders
+    descriptions: Option<Annotations>,
+    quoting: Quoting, // quoting rule of the interpreter
     string: String,
+    types: Option<Annotations>,
+}
+
+// currently supports {1}, {1?}, {1..}, {1..2}, {1..?}, {-1}, {..-1}, {1..-1}, {-2..},
+// followed by modifiers such as `|join:,`
+pub(crate) fn variable() -> Regex {
+    Regex::new(
+        r"\{(?P<body>(?P<index>-?\d+)(?P<optional_idx>\?)?|(?P<start>-?\d+)\.\.((?P<end>(-?\d+|\?)))?|\.\.(?P<until>-\d+))(\|(?P<modifier>join|each):(?P<format>[^{}]*))?\}",
+    )
+    .unwrap()
+}
+
+// quoted words of arguments, `join:,` joins them into a word and `each:-I %` repeats the format,
+// arguments inside of quotes of the template, e.g. `"{0}"`, are not quoted again
+fn expand(params: &[String], captures: &Captures, quoting: Quoting, quoted: bool) -> String {
+    let quote = |value: &str| match quoted {
+        true => value.to_owned(),
+        false => quoting.quote(value),
+    };
+    let format = captures.name("format").map_or("", |w| w.as_str());
+    let words: Vec<String> = match captures.name("modifier").map(|w| w.as_str()) {
+        _ if params.is_empty() => vec![],
+        Some("join") => vec![quote(&params.join(format))],
+        Some("each") => params
+            .iter()
+            .map(|w| format.replace('%', &quote(w)))
+            .collect(),
+        _ => params.iter().map(|w| quote(w)).collect(),
+    };
+
+    placeholders::escape(&words.join(" "))
+}
+
+/**
+ * descriptions or types for parameters, ordered by position or keyed by placeholder
+ */
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum Annotations {
+    // `['application name']`, n-th description is for the parameter starts at n
+    List(Vec<String>),
+
+    // `{ '0' = 'application name', '1..' = 'files' }`, keyed by placeholder without braces
+    Table(BTreeMap<String, String>),
+}
+
+impl Annotations {
+    // `key` is the placeholder without braces, e.g. `1..`, `start` is none for from-end ones
+    pub fn get(&self, start: Option<usize>, key: &str) -> Option<&str> {
+        match (self, start) {
+            (Annotations::List(values), Some(start)) => values.get(start),
+            (Annotations::List(_), None) => None,
+            (Annotations::Table(values), Some(start)) => {
+                values.get(key).or_else(|| values.get(&start.to_string()))
+            }
+            (Annotations::Table(values), None) => values.get(key),
+        }
+        .map(|s| s.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Annotations::List(values) => values.len(),
+            Annotations::Table(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // keys of the table, empty for list
+    pub fn keys(&self) -> Vec<&str> {
+        match self {
+            Annotations::List(_) => vec![],
+            Annotations::Table(values) => values.keys().map(|s| s.as_str()).collect(),
+        }
+    }
+}
+
+/**
+ * constraint for values of a parameter, e.g. `int` or `enum:start|stop`
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParamType {
+    Int,
+    Path,
+    ExistingFile,
+    Directory,
+    Choices(Vec<String>),
+    Pattern(String),
+}
+
+impl ParamType {
+    pub fn parse(string: &str) -> Result<ParamType, String> {
+        match string.trim() {
+            "int" => Ok(ParamType::Int),
+            "path" => Ok(ParamType::Path),
+            "existing-file" => Ok(ParamType::ExistingFile),
+            "directory" => Ok(ParamType::Directory),
+            value => {
+                if let Some(choices) = value.strip_prefix("enum:") {
+                    let choices = choices.split('|').map(|s| s.to_owned()).collect();
+                    Ok(ParamType::Choices(choices))
+                } else if let Some(pattern) = value.strip_prefix("regex:") {
+                    match Regex::new(pattern) {
+                        Ok(_) => Ok(ParamType::Pattern(pattern.to_owned())),
+                        Err(e) => Err(format!("invalid regex `{}`: {}", pattern, e)),
+                    }
+                } else {
+                    Err(format!("unknown type: {}", value))
+                }
+            }
+        }
+    }
+
+    // `path` may not exist yet, e.g. an output file, but its directory must exist
+    pub fn accepts(&self, value: &str) -> bool {
+        match self {
+            ParamType::Int => value.parse::<i64>().is_ok(),
+            ParamType::Path => is_path(value),
+            ParamType::ExistingFile => path::Path::new(value).is_file(),
+            ParamType::Directory => path::Path::new(value).is_dir(),
+            ParamType::Choices(choices) => choices.iter().any(|w| w == value),
+            ParamType::Pattern(pattern) => Regex::new(pattern).unwrap().is_match(value),
+        }
+    }
+
+    // candidates for shell completion
+    pub fn completions(&self) -> Vec<String> {
+        match self {
+            ParamType::Choices(choices) => choices.to_vec(),
+            _ => vec![],
+        }
+    }
+}
+
+// non-empty, without NUL, and the parent is an existing directory (current one if relative)
+fn is_path(value: &str) -> bool {
+    if value.is_empty() || value.contains('\0') {
+        return false;
+    }
+
+    match path::Path::new(value).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.is_dir(),
+        _ => true,
+    }
+}
+
+impl Display for ParamType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParamType::Int => write!(f, "int"),
+            ParamType::Path => write!(f, "path in an existing directory"),
+            ParamType::ExistingFile => write!(f, "existing file"),
+            ParamType::Directory => write!(f, "directory"),
+            ParamType::Choices(choices) => write!(f, "one of {}", choices.join("|")),
+            ParamType::Pattern(pattern) => write!(f, "matches /{}/", pattern),
+        }
+    }
+}
+
+// position of an argument, `End(1)` is the last one
+#[derive(Clone, Copy)]
+enum Index {
+    Start(usize),
+    End(usize),
 }
 
 #[derive(Debug)]
 pub struct Argument {
     capture_str: String,
     description: Option<String>,
+    from_end: Option<usize>, // `range` starts at N-th argument from the end, e.g. `{-2..}`
     is_required: bool,
+    kind: Option<ParamType>,
     range: Range<usize>,
+    reserved: usize, // number of last arguments excluded from unlimited range, e.g. `{..-1}`
 }
 
 impl ArgParser {
-    pub fn new(string: &str, descriptions: Option<Vec<&str>>) -> ArgParser {
-        let descriptions = descriptions.map(|w| w.iter().map(|s| s.to_string()).collect());
-
+    pub fn new(string: &str, descriptions: Option<&Annotations>) -> ArgParser {
         ArgParser {
             arguments: None,
-            descriptions,
+            context: None,
+            descriptions: descriptions.cloned(),
+            quoting: Quoting::default(),
             string: string.to_owned(),
+            types: None,
         }
     }
 
+    // resolves built-in placeholders such as `{name}` and `{env:HOME}` in `fill`
+    pub fn context(mut self, context: Context) -> ArgParser {
+        self.context = Some(context);
+        self
+    }
+
+    pub fn quoting(mut self, quoting: Quoting) -> ArgParser {
+        self.quoting = quoting;
+        self
+    }
+
+    pub fn types(mut self, types: Option<&Annotations>) -> ArgParser {
+        self.types = types.cloned();
+        self
+    }
+
     // accessors
     pub fn arguments(&mut self) -> Option<&Vec<Argument>> {
         self.arguments.as_ref()
     }
 
-    pub fn has_arguments(&self) -> Result<bool, failure::Error> {
+    pub fn has_arguments(&self) -> Result<bool, Error> {
         match &self.arguments {
             Some(value) => Ok(!value.is_empty()),
-            None => Err(failure::err_msg("could not found parsed caches")),
+            None => Err(self.template_error("could not found parsed caches")),
         }
     }
 
     // methods
-    pub fn parse(&mut self) -> Result<(), failure::Error> {
-        // currently supports {1}, {1?}, {1..}, {1..2}, {1..?}
-        let variable = Regex::new(
-            r"\{((?P<index>\d+)(?P<optional_idx>\?)?|(?P<start>\d+)\.\.((?P<end>(\d+|\?)))?)\}",
-        )
-        .unwrap();
-
-        if !variable.is_match(&self.string) {
+    pub fn parse(&mut self) -> Result<(), Error> {
+        // escaped placeholders such as `{{0}}` are not parameters
+        let string = placeholders::strip_escaped(&self.string);
+        let variable = variable();
+
+        if !variable.is_match(&string) {
             self.arguments = Some(vec![]); // no argument(s)
             return Ok(());
         }
 
         let mut arguments: Vec<Argument> = vec![];
-        for capture in variable.captures_iter(&self.string) {
+        for capture in variable.captures_iter(&string) {
             if capture.name("index").is_some() {
                 arguments.push(self.parse_single_index(capture)?);
-            } else if capture.name("start").is_some() {
+            } else if capture.name("start").is_some() || capture.name("until").is_some() {
                 arguments.push(self.parse_range_index(capture)?);
             } else {
-                return Err(failure::err_msg("not implemented yet (unknown pattern)"));
+                return Err(self.template_error("not implemented yet (unknown pattern)"));
             }
         }
 
-        arguments.sort_by_key(|w| w.range.start);
+        // from-end parameters follow others, e.g. `{0} {..-1} {-2} {-1}`
+        arguments.sort_by_key(|w| (w.from_end.map(Reverse), w.range.start));
         self.arguments = Some(
             arguments
                 .into_iter()
@@ -75,97 +271,219 @@ impl ArgParser {
         Ok(())
     }
 
-    fn parse_single_index(&self, captures: Captures) -> Result<Argument, failure::Error> {
-        let index: usize = captures.name("index").unwrap().as_str().parse().unwrap();
+    fn parse_single_index(&self, captures: Captures) -> Result<Argument, Error> {
+        let capture_str = &format!("{{{}}}", &captures["body"]); // without modifiers
+        let index = self.parse_index(&captures["index"])?;
         let optional: bool = match captures.name("optional_idx") {
             Some(value) => value.as_str() == "?", // always true?
             None => false,
         };
-        let description = match &self.descriptions {
-            Some(values) => values.get(index).map(|s| s.to_owned()),
-            None => None,
+
+        // `{-1}` is the last argument
+        let (start, from_end) = match index {
+            Index::Start(index) => (Some(index), None),
+            Index::End(index) => (None, Some(index)),
         };
 
         Ok(Argument {
-            capture_str: captures.get(0).unwrap().as_str().to_owned(),
-            description,
+            capture_str: capture_str.to_owned(),
+            description: self.description(start, capture_str),
+            from_end,
             is_required: !optional,
+            kind: self.kind(start, capture_str)?,
             #[allow(clippy::range_plus_one)]
-            range: (index..(index + 1)),
+            range: start.map_or(0..1, |w| w..(w + 1)),
+            reserved: 0,
         })
     }
 
-    fn parse_range_index(&self, captures: Captures) -> Result<Argument, failure::Error> {
-        let start: usize = captures.name("start").unwrap().as_str().parse().unwrap();
-        let end = captures
-            .name("end")
-            .map_or_else(|| "-1".to_owned(), |w| w.as_str().to_owned());
-
-        if let Ok(index) = end.parse::<usize>() {
-            Ok(Argument {
-                capture_str: captures.get(0).unwrap().as_str().to_owned(),
-                description: None, // not supported yet
-                is_required: true,
-                range: (start..index),
-            })
-        } else if end == "?" {
-            Ok(Argument {
-                capture_str: captures.get(0).unwrap().as_str().to_owned(),
-                description: None, // not supported yet
-                is_required: false,
-                range: (start..std::usize::MAX),
-            })
-        } else if end == "-1" {
-            Ok(Argument {
-                capture_str: captures.get(0).unwrap().as_str().to_owned(),
-                description: None, // not supported yet
-                is_required: true,
-                range: (start..std::usize::MAX),
-            })
-        } else {
-            Err(failure::err_msg(format!("invalid accessor: {}", end)))
-        }
+    fn parse_range_index(&self, captures: Captures) -> Result<Argument, Error> {
+        let capture_str = &format!("{{{}}}", &captures["body"]); // without modifiers
+        let start = match captures.name("start") {
+            Some(value) => self.parse_index(value.as_str())?,
+            None => Index::Start(0), // `{..-1}`
+        };
+        let end = match captures.name("end").or_else(|| captures.name("until")) {
+            Some(value) if value.as_str() == "?" => None,
+            Some(value) => Some(self.parse_index(value.as_str())?),
+            None => None,
+        };
+        let optional = captures.name("end").is_some_and(|w| w.as_str() == "?");
+
+        // (range, from_end, reserved)
+        let (range, from_end, reserved) = match (start, end) {
+            (Index::Start(start), None) => (start..usize::MAX, None, 0),
+            (Index::Start(start), Some(Index::Start(end))) => (start..end, None, 0),
+            (Index::Start(start), Some(Index::End(end))) => (start..usize::MAX, None, end),
+            (Index::End(start), None) if !optional => (0..start, Some(start), 0),
+            (Index::End(start), Some(Index::End(end))) if end < start => {
+                (0..(start - end), Some(start), 0)
+            }
+            _ => {
+                let message = format!("invalid accessor: {}", capture_str);
+                return Err(self.template_error(&message));
+            }
+        };
+
+        let start = match from_end {
+            Some(_) => None,
+            None => Some(range.start),
+        };
+
+        Ok(Argument {
+            capture_str: capture_str.to_owned(),
+            description: self.description(start, capture_str),
+            from_end,
+            is_required: !optional,
+            kind: self.kind(start, capture_str)?,
+            range,
+            reserved,
+        })
     }
 
-    pub fn fill<T>(&mut self, variables: Vec<T>) -> Result<String, failure::Error>
+    // `1` or `-1`
+    fn parse_index(&self, value: &str) -> Result<Index, Error> {
+        let index = match value.strip_prefix('-') {
+            Some(index) => index.parse().ok().filter(|w| *w > 0).map(Index::End),
+            None => value.parse().ok().map(Index::Start),
+        };
+
+        index.ok_or_else(|| self.template_error(&format!("invalid index: {}", value)))
+    }
+
+    pub fn fill<T>(&mut self, variables: Vec<T>) -> Result<String, Error>
     where
         T: Display,
     {
         let arguments = match &self.arguments {
             Some(values) => values,
-            None => return Err(failure::err_msg("could not found parsed caches.")),
+            None => return Err(self.template_error("could not found parsed caches.")),
         };
 
-        let mut replaced = self.string.to_owned();
-        for argument in arguments {
-            let actual = if argument.is_unlimited_range() {
-                variables.len()
-            } else {
-                argument.range.end
-            };
+        // built-ins are resolved first, values are escaped so that they are never replaced again
+        let resolved = match &self.context {
+            Some(context) => placeholders::resolve(&self.string, context, self.quoting)?,
+            None => self.string.to_owned(),
+        };
 
-            let params = match variables.get(argument.range.start..actual) {
+        let mut values = BTreeMap::new();
+        for argument in arguments {
+            let params = match variables.get(argument.slice(variables.len())) {
                 Some(values) => values
                     .iter()
                     .map(|s| s.to_string())
                     .collect::<Vec<String>>(),
-                None => {
-                    if argument.is_required {
-                        return Err(failure::err_msg("index out of bounds or invalid access"));
-                    } else {
-                        vec![]
-                    }
-                }
+                None => vec![],
             };
 
+            if let Some(kind) = &argument.kind {
+                if let Some(value) = params.iter().find(|w| !kind.accepts(w)) {
+                    return Err(Error::ArgumentInvalid {
+                        placeholder: argument.capture_str.to_owned(),
+                        value: value.to_owned(),
+                        expected: kind.to_string(),
+                    });
+                }
+            }
+
             if argument.is_required && params.is_empty() {
-                return Err(failure::err_msg("argument is empty"));
+                return Err(Error::ArgumentMissing {
+                    placeholder: argument.capture_str.to_owned(),
+                    description: argument
+                        .description
+                        .as_ref()
+                        .map(|w| fmt::to_single_line(w)),
+                    given: variables.len(),
+                    required: self.required_count(),
+                    usage: None,
+                });
             }
 
-            replaced = replaced.replace(&argument.capture_str, params.join(" ").as_str());
+            values.insert(argument.capture_str.to_owned(), params);
+        }
+
+        let replaced = placeholders::replace_unescaped(
+            &resolved,
+            &variable(),
+            |captures: &Captures, quoted| match values.get(&format!("{{{}}}", &captures["body"])) {
+                Some(params) => expand(params, captures, self.quoting, quoted),
+                None => captures[0].to_owned(),
+            },
+        );
+
+        Ok(placeholders::unescape(&replaced))
+    }
+
+    // e.g. `show-err-logs <application name> [<arg1>...]`
+    pub fn usage(&self, name: &str) -> String {
+        let mut usage = vec![name.to_owned()];
+        if let Some(arguments) = &self.arguments {
+            usage.extend(arguments.iter().map(|w| w.usage()));
+        }
+
+        usage.join(" ")
+    }
+
+    // parameter that receives the argument at `index`
+    pub fn argument_at(&self, index: usize) -> Option<&Argument> {
+        self.arguments.as_ref().and_then(|values| {
+            // from-end parameters are used when no parameter counts from the start
+            values
+                .iter()
+                .find(|w| w.from_end.is_none() && w.range.contains(&index))
+                .or_else(|| values.iter().find(|w| w.from_end.is_some()))
+        })
+    }
+
+    // candidates for the argument at `index`, from the type of the parameter
+    pub fn completions(&self, index: usize) -> Vec<String> {
+        self.argument_at(index)
+            .and_then(|w| w.kind.as_ref())
+            .map_or_else(Vec::new, |w| w.completions())
+    }
+
+    // number of arguments that must be given
+    pub fn required_count(&self) -> usize {
+        self.arguments.as_ref().map_or(0, |values| {
+            values
+                .iter()
+                .filter(|w| w.is_required)
+                .map(|w| match w.from_end {
+                    Some(index) => index,
+                    None if w.is_unlimited_range() => w.range.start + 1 + w.reserved,
+                    None => w.range.end,
+                })
+                .max()
+                .unwrap_or(0)
+        })
+    }
+
+    fn description(&self, start: Option<usize>, capture_str: &str) -> Option<String> {
+        let key = capture_str.trim_start_matches('{').trim_end_matches('}');
+        self.descriptions
+            .as_ref()
+            .and_then(|w| w.get(start, key))
+            .map(|s| s.to_owned())
+    }
+
+    fn kind(&self, start: Option<usize>, capture_str: &str) -> Result<Option<ParamType>, Error> {
+        let key = capture_str.trim_start_matches('{').trim_end_matches('}');
+        // empty string skips the parameter in list, e.g. `['', 'int']`
+        match self.types.as_ref().and_then(|w| w.get(start, key)) {
+            Some(value) if value.trim().is_empty() => Ok(None),
+            Some(value) => match ParamType::parse(value) {
+                Ok(kind) => Ok(Some(kind)),
+                Err(e) => Err(self.template_error(&e)),
+            },
+            None => Ok(None),
         }
+    }
 
-        Ok(replaced.to_owned())
+    fn template_error(&self, message: &str) -> Error {
+        Error::Template {
+            command: self.string.to_owned(),
+            message: message.to_owned(),
+        }
     }
 }
 
@@ -183,6 +501,46 @@ impl Argument {
         }
     }
 
+    // position of the parameter, e.g. `0`, `1..` or `-1`
+    pub fn label(&self) -> String {
+        if self.is_range() || self.from_end.is_some() {
+            self.capture_str
+                .trim_start_matches('{')
+                .trim_end_matches('}')
+                .to_owned()
+        } else {
+            self.range.start.to_string()
+        }
+    }
+
+    // whether `key` of descriptions table refers to this parameter
+    pub fn matches(&self, key: &str) -> bool {
+        key == self.label() || (self.from_end.is_none() && key == self.range.start.to_string())
+    }
+
+    // number of accepted arguments for range parameters, e.g. `2 arguments` or `1 or more`
+    pub fn count(&self) -> Option<String> {
+        if !self.is_range() {
+            return None;
+        }
+
+        let min = if self.is_required { 1 } else { 0 };
+        if self.is_unlimited_range() {
+            return Some(format!("{} or more", min));
+        }
+
+        let max = self.range.len();
+        match (self.is_required, max) {
+            (true, 1) => Some("1 argument".to_owned()),
+            (true, _) => Some(format!("{} arguments", max)),
+            (false, _) => Some(format!("0 to {}", max)),
+        }
+    }
+
+    pub fn kind(&self) -> Option<&ParamType> {
+        self.kind.as_ref()
+    }
+
     pub fn attribute(&self) -> &str {
         if self.is_required {
             "required"
@@ -191,8 +549,47 @@ impl Argument {
         }
     }
 
+    fn usage(&self) -> String {
+        let label = match &self.description {
+            Some(value) => format!("<{}>", fmt::to_single_line(value)),
+            None => match self.from_end {
+                Some(index) => format!("<arg-{}>", index),
+                None => format!("<arg{}>", self.range.start),
+            },
+        };
+        let label = if self.is_range() {
+            format!("{}...", label)
+        } else {
+            label
+        };
+
+        if self.is_required {
+            label
+        } else {
+            format!("[{}]", label)
+        }
+    }
+
+    // indices of arguments for this parameter, when `len` arguments are given
+    fn slice(&self, len: usize) -> Range<usize> {
+        match self.from_end {
+            Some(index) if index <= len => {
+                (len - index + self.range.start)..(len - index + self.range.end)
+            }
+            Some(_) => 0..0,
+            None if self.is_unlimited_range() => {
+                self.range.start..len.saturating_sub(self.reserved).max(self.range.start)
+            }
+            None => self.range.clone(),
+        }
+    }
+
+    fn is_range(&self) -> bool {
+        self.capture_str.contains("..")
+    }
+
     fn is_unlimited_range(&self) -> bool {
-        self.range.end == std::usize::MAX
+        self.range.end == usize::MAX
     }
 
     fn is_optional_range(&self) -> bool {
@@ -203,26 +600,32 @@ impl Argument {
 #[cfg(test)]
 impl Clone for Argument {
     fn clone(&self) -> Self {
-        let description = match &self.description {
-            Some(value) => Some(value.to_owned()),
-            None => None,
-        };
+        let description = self.description.as_ref().map(|value| value.to_owned());
 
         Argument {
             capture_str: self.capture_str.to_owned(),
             description,
-            is_required: if self.is_required { true } else { false },
+            from_end: self.from_end,
+            is_required: self.is_required,
+            kind: self.kind.clone(),
             range: Range {
                 start: self.range.start,
                 end: self.range.end,
             },
+            reserved: self.reserved,
         }
     }
 }
 
 #[cfg(test)]
+#[allow(
+    clippy::bool_assert_comparison,
+    clippy::legacy_numeric_constants,
+    clippy::map_clone
+)]
 mod tests {
-    use super::{ArgParser, Argument};
+    use super::{Annotations, ArgParser, Argument};
+    use crate::placeholders::Context;
     use std::ops::Range;
     use std::usize;
 
@@ -239,6 +642,10 @@ mod tests {
             .collect())
     }
 
+    fn list(values: &[&str]) -> Annotations {
+        Annotations::List(values.iter().map(|s| s.to_string()).collect())
+    }
+
     fn unlimited_range(start: usize) -> Range<usize> {
         Range {
             start,
@@ -323,9 +730,184 @@ mod tests {
 
         assert_eq!(arguments.len(), 0);
 
-        // no matches
-        let arguments = initialize_and_parsed("{-1}").unwrap();
+        // from the end
+        let arguments = initialize_and_parsed("cp {..-1} {-1}").unwrap();
 
-        assert_eq!(arguments.len(), 0);
+        assert_eq!(arguments.len(), 2);
+        assert_eq!(arguments[0].capture_str, "{..-1}");
+        assert_eq!(arguments[0].reserved, 1);
+        assert_eq!(arguments[1].capture_str, "{-1}");
+        assert_eq!(arguments[1].from_end, Some(1));
+
+        // invalid accessors
+        assert!(initialize_and_parsed("{-0}").is_err());
+        assert!(initialize_and_parsed("{-1..2}").is_err());
+        assert!(initialize_and_parsed("{-1..-2}").is_err());
+    }
+
+    #[test]
+    fn fill_from_end() {
+        let mut parser = ArgParser::new("cp {..-1} {-1}", None);
+        parser.parse().unwrap();
+
+        assert_eq!(parser.required_count(), 2);
+        assert_eq!(parser.usage("copy"), "copy <arg0>... <arg-1>");
+        assert_eq!(parser.fill(vec!["a", "b", "dest"]).unwrap(), "cp a b dest");
+        assert_eq!(
+            parser.fill(vec!["dest"]).unwrap_err().to_string(),
+            "missing argument {..-1}: 1 given, but 2 required, please use `show` subcommand for checking parameters"
+        );
+
+        let mut parser = ArgParser::new("echo {1..-1} {-2..} {-3..-1} {-4?}", None);
+        parser.parse().unwrap();
+
+        assert_eq!(parser.required_count(), 3);
+        assert_eq!(
+            parser.fill(vec!["a", "b", "c", "d"]).unwrap(),
+            "echo b c c d b c a"
+        );
+        assert_eq!(parser.fill(vec!["a", "b", "c"]).unwrap(), "echo b b c a b ");
+    }
+
+    #[test]
+    fn fill() {
+        let mut parser = ArgParser::new("cp {0} {1} {2..?}", Some(&list(&["source"])));
+        parser.parse().unwrap();
+
+        assert_eq!(parser.fill(vec!["a", "b"]).unwrap(), "cp a b ");
+        assert_eq!(parser.fill(vec!["a", "b", "c", "d"]).unwrap(), "cp a b c d");
+        assert_eq!(
+            parser.fill(vec!["a"]).unwrap_err().to_string(),
+            "missing argument {1}: 1 given, but 2 required, please use `show` subcommand for checking parameters"
+        );
+    }
+
+    #[test]
+    fn usage() {
+        let mut parser = ArgParser::new("cp {0} {1?} {2..}", Some(&list(&["source"])));
+        parser.parse().unwrap();
+
+        assert_eq!(parser.usage("copy"), "copy <source> [<arg1>] <arg2>...");
+        assert_eq!(parser.required_count(), 3);
+    }
+
+    #[test]
+    fn describe_range() {
+        let mut parser = ArgParser::new("cp {0..2} {2..?}", Some(&list(&["files", "", "extra"])));
+        parser.parse().unwrap();
+
+        let arguments = parser.arguments().unwrap();
+        assert_eq!(arguments[0].description(), "files");
+        assert_eq!(arguments[0].label(), "0..2");
+        assert_eq!(arguments[0].count().unwrap(), "2 arguments");
+        assert_eq!(arguments[1].description(), "extra");
+        assert_eq!(arguments[1].count().unwrap(), "0 or more");
+
+        let table: Annotations = toml::from_str("'0' = 'target'\n'1..' = 'files'").unwrap();
+        let mut parser = ArgParser::new("tar -cf {0} {1..}", Some(&table));
+        parser.parse().unwrap();
+
+        let arguments = parser.arguments().unwrap();
+        assert_eq!(arguments[0].description(), "target");
+        assert_eq!(arguments[0].count(), None);
+        assert_eq!(arguments[1].description(), "files");
+        assert_eq!(arguments[1].count().unwrap(), "1 or more");
+    }
+
+    #[test]
+    fn fill_escaped() {
+        let fill = |template: &str, args: Vec<&str>| {
+            let mut parser = ArgParser::new(template, None).context(Context::new("items", None));
+            parser.parse().unwrap();
+            (parser.required_count(), parser.fill(args).unwrap())
+        };
+
+        assert_eq!(
+            fill("awk '/{{0}}/ {print ${0}}' {1}", vec!["2", "a.txt"]),
+            (2, "awk '/{0}/ {print $2}' a.txt".to_owned())
+        );
+        assert_eq!(
+            fill("find {0} | xargs -I{{0}} mv {{0}} {{0}}.bak", vec!["logs"]),
+            (1, "find logs | xargs -I{0} mv {0} {0}.bak".to_owned())
+        );
+        assert_eq!(
+            fill("jq '.{name}[{0}] | {{name}}'", vec!["1"]),
+            (1, "jq '.items[1] | {name}'".to_owned())
+        );
+
+        // substituted values are never replaced again
+        assert_eq!(fill("echo {0} {1}", vec!["{1}", "x"]).1, "echo '{1}' x");
+    }
+
+    #[test]
+    fn fill_words() {
+        let files = vec!["a.txt", "my notes.txt", "it's.txt", ""];
+        let fill = |template: &str| {
+            let mut parser = ArgParser::new(template, None);
+            parser.parse().unwrap();
+            parser.fill(files.to_owned()).unwrap()
+        };
+
+        assert_eq!(
+            fill("cat {0..}"),
+            "cat a.txt 'my notes.txt' 'it'\\''s.txt' ''"
+        );
+        assert_eq!(
+            fill("tar -cf {0} {1..3|join:,}"),
+            "tar -cf a.txt 'my notes.txt,it'\\''s.txt'"
+        );
+        assert_eq!(
+            fill("gcc {0..2|each:-I %} {0..?}"),
+            "gcc -I a.txt -I 'my notes.txt' a.txt 'my notes.txt' 'it'\\''s.txt' ''"
+        );
+        assert_eq!(fill("ls {4..?|each:-I %}"), "ls ");
+
+        // already quoted by the template
+        assert_eq!(
+            fill("echo \"{1}\" '{0..1}'"),
+            "echo \"my notes.txt\" 'a.txt'"
+        );
+        assert_eq!(
+            fill("echo \"{0..2|join:, }\" \\\"{1}"),
+            "echo \"a.txt, my notes.txt\" \\\"'my notes.txt'"
+        );
+    }
+
+    #[test]
+    fn fill_typed() {
+        let types = list(&["int", "enum:start|stop", "directory"]);
+        let mut parser = ArgParser::new("run {0} {1} {2..?}", None).types(Some(&types));
+        parser.parse().unwrap();
+
+        assert_eq!(parser.fill(vec!["3", "stop", "/"]).unwrap(), "run 3 stop /");
+        assert_eq!(
+            parser.fill(vec!["three", "stop"]).unwrap_err().to_string(),
+            "invalid argument `three` for {0}: expected int"
+        );
+        assert_eq!(
+            parser.fill(vec!["3", "restart"]).unwrap_err().to_string(),
+            "invalid argument `restart` for {1}: expected one of start|stop"
+        );
+        assert_eq!(parser.completions(1), vec!["start", "stop"]);
+        assert!(parser.completions(0).is_empty());
+
+        let types = list(&["path"]);
+        let mut parser = ArgParser::new("touch {0}", None).types(Some(&types));
+        parser.parse().unwrap();
+        assert!(parser.fill(vec!["/tmp/new file"]).is_ok());
+        assert!(parser.fill(vec!["new file"]).is_ok());
+        assert_eq!(
+            parser.fill(vec!["/nowhere/file"]).unwrap_err().to_string(),
+            "invalid argument `/nowhere/file` for {0}: expected path in an existing directory"
+        );
+        assert!(parser.fill(vec!["a\0b"]).is_err());
+        assert!(parser.fill(vec![""]).is_err());
+
+        let types = list(&["regex:^v\\d+$", "float"]);
+        let mut parser = ArgParser::new("run {0} {1}", None).types(Some(&types));
+        assert_eq!(
+            parser.parse().unwrap_err().to_string(),
+            "unknown type: float"
+        );
     }
 }
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output};
+
+#[cfg(unix)]
+use crate::signal;
+
+/**
+ * a process to be spawned by the backend
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Invocation {
+    pub program: String,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>, // additional environment variables
+    pub cwd: Option<PathBuf>,          // working directory, inherited if not set
+}
+
+impl Invocation {
+    pub fn new(program: &str, args: &[&str]) -> Invocation {
+        Invocation {
+            program: program.to_owned(),
+            args: args.iter().map(|s| s.to_string()).collect(),
+            env: BTreeMap::new(),
+            cwd: None,
+        }
+    }
+
+    pub fn current_dir(mut self, cwd: Option<&Path>) -> Invocation {
+        self.cwd = cwd.map(|w| w.to_owned());
+        self
+    }
+
+    fn to_command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args).envs(&self.env);
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+        command
+    }
+}
+
+/**
+ * backend decides how the function is actually executed
+ */
+pub trait Backend {
+    fn run(&mut self, invocation: &Invocation) -> io::Result<ExitStatus>;
+}
+
+impl<T: Backend + ?Sized> Backend for &mut T {
+    fn run(&mut self, invocation: &Invocation) -> io::Result<ExitStatus> {
+        (**self).run(invocation)
+    }
+}
+
+/**
+ * spawns the process with inherited stdio, default for `cet exec`
+ */
+#[derive(Debug, Default)]
+pub struct Inherit;
+
+impl Backend for Inherit {
+    fn run(&mut self, invocation: &Invocation) -> io::Result<ExitStatus> {
+        #[cfg(unix)]
+        {
+            let forward = signal::Forward::new();
+            let mut command = invocation.to_command();
+            forward.isolate(&mut command);
+            let child = command.spawn()?;
+            forward.wait(child.id())
+        }
+
+        #[cfg(not(unix))]
+        invocation.to_command().spawn()?.wait()
+    }
+}
+
+/**
+ * spawns the process and captures its stdout and stderr
+ */
+#[derive(Debug, Default)]
+pub struct Capture {
+    pub outputs: Vec<Output>,
+}
+
+impl Capture {
+    pub fn new() -> Capture {
+        Capture::default()
+    }
+
+    pub fn last(&self) -> Option<&Output> {
+        self.outputs.last()
+    }
+}
+
+impl Backend for Capture {
+    fn run(&mut self, invocation: &Invocation) -> io::Result<ExitStatus> {
+        let output = invocation.to_command().output()?;
+        let status = output.status;
+        self.outputs.push(output);
+
+        Ok(status)
+    }
+}
+
+/**
+ * records invocations without spawning anything, for testing
+ */
+#[derive(Debug, Default)]
+pub struct Recorder {
+    pub invocations: Vec<Invocation>,
+    code: i32,
+}
+
+impl Recorder {
+    // every invocation exits with the given code
+    pub fn new(code: i32) -> Recorder {
+        Recorder {
+            invocations: vec![],
+            code,
+        }
+    }
+}
+
+impl Backend for Recorder {
+    fn run(&mut self, invocation: &Invocation) -> io::Result<ExitStatus> {
+        self.invocations.push(invocation.clone());
+
+        Ok(exit_status(self.code))
+    }
+}
+
+#[cfg(unix)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    ExitStatus::from_raw(code << 8)
+}
+
+#[cfg(windows)]
+fn exit_status(code: i32) -> ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    ExitStatus::from_raw(code as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Backend, Capture, Invocation, Recorder};
+
+    #[test]
+    fn capture() {
+        let mut backend = Capture::new();
+        let status = backend
+            .run(&Invocation::new(
+                "sh",
+                &["-c", "echo out; echo err >&2; exit 2"],
+            ))
+            .unwrap();
+
+        assert_eq!(status.code(), Some(2));
+        assert_eq!(backend.last().unwrap().stdout, b"out\n");
+        assert_eq!(backend.last().unwrap().stderr, b"err\n");
+    }
+
+    #[test]
+    fn recorder() {
+        let mut backend = Recorder::new(5);
+        let invocation = Invocation::new("sh", &["-c", "command ls"]);
+        let status = backend.run(&invocation).unwrap();
+
+        assert_eq!(status.code(), Some(5));
+        assert_eq!(backend.invocations, vec![invocation]);
+    }
+}
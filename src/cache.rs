@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::interpreter::Registry;
+use crate::shell;
+use crate::shell::Unavailable;
+
+// serializes read-modify-write of the cache file, conditions are evaluated in parallel by `cet init`
+static LOCK: Mutex<()> = Mutex::new(());
+
+/**
+ * cached result of a condition, keyed by shell, condition and $PATH
+ */
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Entry {
+    checked_at: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<i32>, // None if terminated by signal
+
+    #[serde(skip_serializing_if = "String::is_empty", default)]
+    stderr: String,
+
+    #[serde(default)]
+    ttl: u64, // seconds, expired entries are dropped when saving
+}
+
+// same as `shell::probe`, but reuses the result of the condition while it is younger than ttl
+pub fn probe(
+    interpreters: &Registry,
+    shell: &str,
+    condition: &Option<String>,
+    ttl: &Option<String>,
+) -> Result<(), Unavailable> {
+    let (condition, ttl) = match (condition, ttl.as_ref().map(|w| parse_ttl(w))) {
+        (Some(condition), Some(Ok(ttl))) => (condition, ttl),
+        _ => return shell::probe(interpreters, shell, condition),
+    };
+
+    let key = create_key(shell, condition);
+    let now = now();
+    let cached = {
+        let _lock = LOCK.lock();
+        load().remove(&key)
+    };
+
+    if let Some(entry) = cached {
+        if now.saturating_sub(entry.checked_at) < ttl {
+            return to_result(condition, &entry);
+        }
+    }
+
+    let result = shell::probe(interpreters, shell, &Some(condition.to_owned()));
+    let entry = match &result {
+        Ok(_) => Entry {
+            checked_at: now,
+            code: Some(0),
+            stderr: String::new(),
+            ttl,
+        },
+        Err(Unavailable::ConditionFailed { code, stderr, .. }) => Entry {
+            checked_at: now,
+            code: *code,
+            stderr: stderr.to_owned(),
+            ttl,
+        },
+        Err(_) => return result, // missing shell is not cached
+    };
+
+    // re-read the cache, it may be updated by other threads while running the condition
+    let _lock = LOCK.lock();
+    let mut entries = load();
+    entries.retain(|_, w| now.saturating_sub(w.checked_at) < w.ttl);
+    entries.insert(key, entry);
+    save(&entries);
+
+    result
+}
+
+pub fn clear() -> Result<(), failure::Error> {
+    let path = match cache_path() {
+        Some(value) => value,
+        None => return Err(failure::err_msg("could not detect cache path.")),
+    };
+
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+// accepts `30s`, `10m`, `1h` and `7d`, returns seconds
+pub fn parse_ttl(ttl: &str) -> Result<u64, failure::Error> {
+    let ttl = ttl.trim();
+    let (value, unit) = ttl.split_at(ttl.len() - ttl.trim_start_matches(char::is_numeric).len());
+    let value: u64 = match value.parse() {
+        Ok(value) => value,
+        Err(_) => return Err(failure::err_msg(format!("invalid ttl: {}", ttl))),
+    };
+
+    let seconds = match unit {
+        "s" => Some(value),
+        "m" => value.checked_mul(60),
+        "h" => value.checked_mul(60 * 60),
+        "d" => value.checked_mul(60 * 60 * 24),
+        _ => None,
+    };
+
+    seconds.ok_or_else(|| failure::err_msg(format!("invalid ttl: {}", ttl)))
+}
+
+fn to_result(condition: &str, entry: &Entry) -> Result<(), Unavailable> {
+    match entry.code {
+        Some(0) => Ok(()),
+        code => Err(Unavailable::ConditionFailed {
+            condition: condition.to_owned(),
+            code,
+            stderr: entry.stderr.to_owned(),
+        }),
+    }
+}
+
+fn create_key(shell: &str, condition: &str) -> String {
+    let path = env::var("PATH").unwrap_or_default();
+    format!("{:016x}", fnv1a(&[shell, condition, &path].join("\0")))
+}
+
+// FNV-1a, which is stable across builds and releases unlike `DefaultHasher`
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |w| w.as_secs())
+}
+
+fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|w| w.join("centoria").join("conditions.toml"))
+}
+
+// cache is best-effort, so broken or missing file is treated as empty
+fn load() -> BTreeMap<String, Entry> {
+    cache_path()
+        .and_then(|w| fs::read_to_string(w).ok())
+        .and_then(|w| toml::from_str(&w).ok())
+        .unwrap_or_default()
+}
+
+fn save(entries: &BTreeMap<String, Entry>) {
+    let path = match cache_path() {
+        Some(value) => value,
+        None => return,
+    };
+    let toml_str = match toml::to_string(entries) {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+
+    // write to temporary file and rename it, for avoiding torn writes
+    let temp = path.with_extension(format!("toml.{}", std::process::id()));
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if fs::write(&temp, toml_str).is_ok() {
+        let _ = fs::rename(&temp, &path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::sync::Mutex;
+
+    use tempfile::TempDir;
+
+    use super::{cache_path, create_key, fnv1a, load, parse_ttl, probe, save, Entry};
+    use crate::interpreter::Registry;
+
+    // tests touching the cache share `XDG_CACHE_HOME`, so that they are run one by one
+    static CACHE_HOME: Mutex<()> = Mutex::new(());
+
+    // runs the test with an empty cache, and a condition counting its evaluations
+    fn with_cache<F>(test: F)
+    where
+        F: FnOnce(Option<String>, &dyn Fn() -> usize),
+    {
+        let _lock = CACHE_HOME.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = TempDir::new().unwrap();
+        env::set_var("XDG_CACHE_HOME", dir.path());
+        assert!(cache_path().unwrap().starts_with(dir.path()));
+
+        let counter = dir.path().join("counter");
+        let condition = Some(format!("echo >> '{}'", counter.display()));
+        test(condition, &|| {
+            fs::read_to_string(&counter).map_or(0, |w| w.lines().count())
+        });
+    }
+
+    fn hour() -> Option<String> {
+        Some("1h".to_owned())
+    }
+
+    #[test]
+    fn probe_within_ttl() {
+        with_cache(|condition, count| {
+            let registry = Registry::default();
+            assert!(probe(&registry, "sh", &condition, &hour()).is_ok());
+            assert!(probe(&registry, "sh", &condition, &hour()).is_ok());
+            assert_eq!(count(), 1);
+            assert_eq!(load().len(), 1);
+        });
+    }
+
+    #[test]
+    fn probe_after_expiry() {
+        with_cache(|condition, count| {
+            let registry = Registry::default();
+            assert!(probe(&registry, "sh", &condition, &hour()).is_ok());
+
+            let key = create_key("sh", condition.as_ref().unwrap());
+            let mut entries = load();
+            entries.get_mut(&key).unwrap().checked_at -= 3600;
+            save(&entries);
+
+            assert!(probe(&registry, "sh", &condition, &hour()).is_ok());
+            assert_eq!(count(), 2);
+        });
+    }
+
+    #[test]
+    fn probe_without_ttl() {
+        with_cache(|condition, count| {
+            let registry = Registry::default();
+            assert!(probe(&registry, "sh", &condition, &None).is_ok());
+            assert!(probe(&registry, "sh", &condition, &None).is_ok());
+            assert_eq!(count(), 2);
+            assert!(load().is_empty());
+        });
+    }
+
+    #[test]
+    fn probe_missing_shell() {
+        with_cache(|condition, count| {
+            let registry = Registry::default();
+            let shell = "centoria-nonexistent-shell";
+            assert!(probe(&registry, shell, &condition, &hour()).is_err());
+            assert_eq!(count(), 0);
+            assert!(load().is_empty());
+        });
+    }
+
+    #[test]
+    fn expired_entries_are_removed() {
+        with_cache(|condition, _| {
+            let expired = Entry {
+                checked_at: 0,
+                code: Some(0),
+                stderr: String::new(),
+                ttl: 60,
+            };
+            save(&std::iter::once(("expired".to_owned(), expired)).collect());
+
+            assert!(probe(&Registry::default(), "sh", &condition, &hour()).is_ok());
+            assert_eq!(
+                load().keys().collect::<Vec<_>>(),
+                vec![&create_key("sh", condition.as_ref().unwrap())]
+            );
+        });
+    }
+
+    #[test]
+    fn parse_ttl_test() {
+        assert_eq!(parse_ttl("30s").unwrap(), 30);
+        assert_eq!(parse_ttl("10m").unwrap(), 600);
+        assert_eq!(parse_ttl("1h").unwrap(), 3600);
+        assert_eq!(parse_ttl("7d").unwrap(), 604_800);
+        assert_eq!(parse_ttl("1w").unwrap_err().to_string(), "invalid ttl: 1w");
+        assert_eq!(parse_ttl("h").unwrap_err().to_string(), "invalid ttl: h");
+        assert_eq!(parse_ttl("").unwrap_err().to_string(), "invalid ttl: ");
+        assert_eq!(
+            parse_ttl("999999999999999999d").unwrap_err().to_string(),
+            "invalid ttl: 999999999999999999d"
+        );
+    }
+
+    #[test]
+    fn fnv1a_test() {
+        assert_eq!(fnv1a(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -1,8 +1,6 @@
 use clap::ArgMatches;
-use regex::Regex;
 
-use crate::config::Config;
-use crate::executors::{Alias, Executor, Function, SubCommand};
+use centoria::{Alias, Config, Executor, Function, SubCommand};
 
 pub fn add(args: &ArgMatches) -> Result<(), failure::Error> {
     if args.value_of("program").is_some() {
@@ -31,9 +29,10 @@ fn add_subcommand(args: &ArgMatches) -> Result<(), failure::Error> {
     let program = args.value_of("program").unwrap();
     let command = args.value_of("command").unwrap();
     let condition = args.value_of("condition");
+    let cwd = args.value_of("cwd");
     let description = args.value_of("description");
     let shell = args.value_of("shell");
-    let executor = SubCommand::new(program, name, command, condition, description, shell);
+    let executor = SubCommand::new(program, name, command, condition, cwd, description, shell);
 
     cfg.add(program, Box::new(executor))?;
     cfg.save()?;
@@ -48,10 +47,9 @@ fn construct(args: &ArgMatches) -> Box<dyn Executor> {
     let description = args.value_of("description");
     let shell = args.value_of("shell");
 
-    let regex = Regex::new(r"\{\d+(\.\.(\d+)?)?\}").unwrap();
-    if regex.is_match(&command) {
-        Box::new(Function::new(&command, condition, cwd, description, shell))
+    if Function::is_template(command) {
+        Box::new(Function::new(command, condition, cwd, description, shell))
     } else {
-        Box::new(Alias::new(&command, condition, cwd, description, shell))
+        Box::new(Alias::new(command, condition, cwd, description, shell))
     }
 }
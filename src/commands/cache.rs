@@ -0,0 +1,11 @@
+use clap::ArgMatches;
+
+pub fn cache(args: &ArgMatches) -> Result<(), failure::Error> {
+    match args.subcommand() {
+        ("clear", Some(_)) => centoria::clear_cache(),
+        _ => {
+            let msg = "subcommand is required";
+            Err(failure::err_msg(msg))
+        }
+    }
+}
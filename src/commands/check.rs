@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use centoria::{Config, Error, Executor, Issue, Registry};
+
+pub fn check() -> Result<(), failure::Error> {
+    let paths: Vec<_> = Config::candidate_paths()
+        .into_iter()
+        .filter(|w| w.exists())
+        .collect();
+
+    if paths.is_empty() {
+        println!("no configuration files found");
+        return Ok(());
+    }
+
+    let mut problems = 0;
+    for path in &paths {
+        let config_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let table = match Config::load_raw(path) {
+            Ok(value) => value,
+            Err(e) => {
+                println!("{}: {}", path.display(), e);
+                problems += 1;
+                continue;
+            }
+        };
+
+        let mut issues: Vec<Issue> = vec![];
+
+        // interpreters are read first, so that functions of the same file can refer them
+        let mut table = table;
+        let interpreters = match table
+            .remove("interpreters")
+            .map(|w| w.try_into::<Registry>())
+        {
+            Some(Ok(interpreters)) => interpreters,
+            Some(Err(e)) => {
+                issues.push(Issue::new("interpreters", &e.to_string()));
+                Registry::default()
+            }
+            None => Registry::default(),
+        };
+
+        for (name, value) in table {
+            match value.try_into::<Box<dyn Executor>>() {
+                Ok(executor) => issues.extend(
+                    executor
+                        .validate(&interpreters, config_dir)
+                        .into_iter()
+                        .map(|w| Issue {
+                            key: format!("{}.{}", name, w.key),
+                            ..w
+                        }),
+                ),
+                Err(e) => issues.push(Issue::new(&name, &e.to_string())),
+            }
+        }
+
+        for issue in &issues {
+            let severity = if issue.is_warning { "warning: " } else { "" };
+            println!(
+                "{}: {}: {}{}",
+                path.display(),
+                issue.key,
+                severity,
+                issue.message
+            );
+        }
+
+        problems += issues.iter().filter(|w| !w.is_warning).count();
+    }
+
+    if problems > 0 {
+        return Err(Error::ConfigInvalid { problems }.into());
+    }
+
+    println!("no problems found in {} file(s)", paths.len());
+    Ok(())
+}
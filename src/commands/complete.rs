@@ -0,0 +1,28 @@
+use clap::ArgMatches;
+
+use centoria::Config;
+
+pub fn complete(args: &ArgMatches) -> Result<(), failure::Error> {
+    let cfg = Config::load()?;
+    let name = args.value_of("name").unwrap();
+    let mut extra: Vec<String> = args
+        .values_of("extra")
+        .map_or_else(Vec::new, |w| w.map(|s| s.to_owned()).collect());
+
+    // completing the first argument
+    if extra.is_empty() {
+        extra.push("".to_owned());
+    }
+
+    // unknown functions have no candidates, shell falls back to default completion
+    if let Some(executor) = cfg.get(name) {
+        let current = extra.last().unwrap().to_owned();
+        for candidate in executor.complete(&extra) {
+            if candidate.starts_with(&current) {
+                println!("{}", candidate);
+            }
+        }
+    }
+
+    Ok(())
+}
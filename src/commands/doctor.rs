@@ -0,0 +1,85 @@
+use std::env;
+use std::path::PathBuf;
+
+use centoria::{Config, SubCommand};
+
+pub fn doctor() -> Result<(), failure::Error> {
+    let selected = Config::find_valid_path()?;
+
+    println!("Configuration:");
+    for path in Config::candidate_paths() {
+        let status = if path == selected && !path.exists() {
+            "selected (will be created)"
+        } else if path == selected {
+            "selected"
+        } else if path.exists() {
+            "skipped (lower priority)"
+        } else {
+            "skipped (not found)"
+        };
+        println!("    {} : {}", path.display(), status);
+    }
+
+    println!("\nShell integration:");
+    match env::var("CENTORIA_INIT") {
+        Ok(ref value) if selected.as_os_str() == value.as_str() => {
+            println!("    loaded from {}", value);
+        }
+        Ok(value) => println!(
+            "    loaded from {}, but {} is selected now, please reload `cet init`",
+            value,
+            selected.display()
+        ),
+        Err(_) => println!("    not loaded in this shell, please run `cet init`"),
+    }
+
+    let config = Config::load()?;
+    let mut unavailable = vec![];
+    let mut shadowed = vec![];
+    for entry in config.keys() {
+        let executor = match config.get(&entry) {
+            Some(value) => value,
+            None => return Err(failure::err_msg("invalid operation")),
+        };
+
+        if let Err(e) = executor.can_execute(config.interpreters()) {
+            match e.stderr() {
+                Some(stderr) => unavailable.push(format!("{} : {}: {}", entry, e, stderr)),
+                None => unavailable.push(format!("{} : {}", entry, e)),
+            }
+        }
+
+        if let Some(path) = find_in_path(&entry) {
+            if executor.is::<SubCommand>() {
+                shadowed.push(format!("{} : wraps {}", entry, path.display()));
+            } else {
+                shadowed.push(format!("{} : shadows {}", entry, path.display()));
+            }
+        }
+    }
+
+    println!("\nUnavailable functions:");
+    print_or_none(&unavailable);
+
+    println!("\nShadowed commands:");
+    print_or_none(&shadowed);
+
+    Ok(())
+}
+
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|w| w.join(name))
+        .find(|w| w.is_file())
+}
+
+fn print_or_none(lines: &[String]) {
+    if lines.is_empty() {
+        println!("    none");
+    }
+
+    for line in lines {
+        println!("    {}", line);
+    }
+}
@@ -1,25 +1,22 @@
+use std::io::{self, IsTerminal};
 use std::process::ExitStatus;
 
 use clap::ArgMatches;
 
-use crate::config::Config;
+use centoria::{Config, ExecutionRequest};
 
 pub fn exec(args: &ArgMatches) -> Result<ExitStatus, failure::Error> {
     let cfg = Config::load()?;
     let name = args.value_of("name").unwrap();
+    let extra: Vec<String> = args
+        .values_of("extra")
+        .map_or_else(Vec::new, |w| w.map(|s| s.to_owned()).collect());
 
-    let executor = match cfg.get(name) {
-        Some(value) => value,
-        None => {
-            let msg = format!("function name `{}` is not exists", name);
-            return Err(failure::err_msg(msg));
-        }
-    };
+    let mut request = ExecutionRequest::new(name, extra)
+        .verbose(args.is_present("verbose"))
+        .dry_run(args.is_present("dry_run"))
+        .prompt(args.is_present("interactive"))
+        .terminal(io::stdin().is_terminal());
 
-    if !executor.can_execute() {
-        let msg = format!("could not execute the function `{}`", name);
-        return Err(failure::err_msg(msg));
-    }
-
-    Ok(executor.execute(args)?)
+    cfg.execute(&mut request)
 }
@@ -1,15 +1,93 @@
-use crate::config::Config;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
-pub fn init() -> Result<(), failure::Error> {
+use clap::ArgMatches;
+
+use centoria::{Config, Executor, Quoting, Registry, Unavailable};
+
+pub fn init(args: &ArgMatches) -> Result<(), failure::Error> {
     let config = Config::load()?;
+    let export_all = args.is_present("all");
+
+    // marker for `cet doctor` to detect the loaded configuration
+    let path = Config::find_valid_path()?;
+    println!(
+        "export CENTORIA_INIT={}",
+        Quoting::Posix.quote(&path.display().to_string())
+    );
+
+    let mut executors = vec![];
     for entry in config.keys() {
-        let export = match config.get(&entry) {
-            Some(value) => value.export_as(&entry)?,
+        match config.get(&entry) {
+            Some(value) => executors.push((entry, value)),
             None => return Err(failure::err_msg("invalid operation")),
         };
+    }
+
+    // results are cached if `condition_ttl` is specified
+    let statuses = if export_all {
+        executors.iter().map(|_| Ok(())).collect()
+    } else {
+        evaluate(&executors, config.interpreters())
+    };
+
+    let skipped: Vec<String> = executors
+        .iter()
+        .zip(statuses.iter())
+        .filter_map(|((entry, _), status)| match status {
+            Ok(_) => None,
+            Err(e) => Some(format!("#   {} : {}", entry, e)),
+        })
+        .collect();
 
-        println!("{}", export);
+    if !skipped.is_empty() {
+        println!("# skipped unavailable functions (use `cet init --all` to export them):");
+        println!("{}", skipped.join("\n"));
+    }
+
+    for ((entry, executor), status) in executors.iter().zip(statuses.iter()) {
+        if status.is_ok() {
+            println!("{}", executor.export_as(entry)?);
+        }
     }
 
     Ok(())
 }
+
+// evaluates conditions in parallel on a pool bounded by the available parallelism
+fn evaluate(
+    executors: &[(String, &dyn Executor)],
+    interpreters: &Registry,
+) -> Vec<Result<(), Unavailable>> {
+    let workers = thread::available_parallelism()
+        .map_or(1, |w| w.get())
+        .min(executors.len());
+    let next = AtomicUsize::new(0);
+
+    let mut statuses: Vec<(usize, Result<(), Unavailable>)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut statuses = vec![];
+                    loop {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        match executors.get(index) {
+                            Some((_, executor)) => {
+                                statuses.push((index, executor.can_execute(interpreters)))
+                            }
+                            None => return statuses,
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|w| w.join().unwrap())
+            .collect()
+    });
+
+    statuses.sort_by_key(|(index, _)| *index);
+    statuses.into_iter().map(|(_, w)| w).collect()
+}
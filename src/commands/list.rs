@@ -1,21 +1,28 @@
-use crate::config::Config;
-use crate::pad;
+use centoria::Config;
 
 pub fn list() -> Result<(), failure::Error> {
     let config = Config::load()?;
     let entries = config.keys();
 
-    let longest = &entries.iter().max_by_key(|w| w.len()).unwrap();
+    let longest = entries.iter().map(|w| w.len()).max().unwrap_or(0);
     for entry in &entries {
-        let description = match config.get(&entry) {
-            Some(value) => value.description(),
+        let executor = match config.get(entry) {
+            Some(value) => value,
             None => return Err(failure::err_msg("invalid operation")),
         };
 
+        // mark functions that could not be executed on this machine
+        let marker = match executor.can_execute(config.interpreters()) {
+            Ok(_) => "",
+            Err(_) => " [unavailable]",
+        };
+
         println!(
-            "{} : {}",
-            pad::right_pad(&entry, longest.len()),
-            description
+            "{:<width$} : {}{}",
+            entry,
+            executor.description(),
+            marker,
+            width = longest
         );
     }
 
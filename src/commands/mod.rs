@@ -1,4 +1,8 @@
 mod add;
+mod cache;
+mod check;
+mod complete;
+mod doctor;
 mod exec;
 mod init;
 mod list;
@@ -6,6 +10,10 @@ mod remove;
 mod show;
 
 pub use add::add;
+pub use cache::cache;
+pub use check::check;
+pub use complete::complete;
+pub use doctor::doctor;
 pub use exec::exec;
 pub use init::init;
 pub use list::list;
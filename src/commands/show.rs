@@ -1,18 +1,39 @@
 use clap::ArgMatches;
 
-use crate::config::Config;
+use centoria::{Config, DisplayRequest, Error, PLACEHOLDERS};
 
 pub fn show(args: &ArgMatches) -> Result<(), failure::Error> {
+    if args.is_present("placeholders") {
+        show_placeholders();
+        return Ok(());
+    }
+
     let cfg = Config::load()?;
     let name = args.value_of("name").unwrap();
-    let executor = match cfg.get(&name) {
+    let executor = match cfg.get(name) {
         Some(value) => value,
         None => {
-            let msg = format!("function `{}` is not exists", &name);
-            return Err(failure::err_msg(msg));
+            let name = name.to_owned();
+            return Err(Error::NotFound { name }.into());
         }
     };
-    executor.display(args)?;
+    let mut request =
+        DisplayRequest::new(name, args.value_of("subcommand")).interpreters(cfg.interpreters());
+    executor.display(&mut request)?;
 
     Ok(())
 }
+
+fn show_placeholders() {
+    let longest = PLACEHOLDERS.iter().map(|(w, _)| w.len()).max().unwrap_or(0);
+
+    println!("Placeholders:");
+    for (placeholder, description) in PLACEHOLDERS.iter() {
+        println!(
+            "    {:<width$} : {}",
+            placeholder,
+            description,
+            width = longest
+        );
+    }
+}
@@ -1,84 +1,135 @@
 use std::collections::BTreeMap;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
 
-use crate::executors::{Executor, SubCommand};
+use crate::error::Error;
+use crate::executors::{ExecutionRequest, Executor, SubCommand};
+use crate::interpreter::Registry;
 
 pub struct Config {
     // value must implement Executor trait
     entries: BTreeMap<String, Box<dyn Executor>>,
+    interpreters: Registry,
+    path: PathBuf,
+}
+
+/**
+ * layout of the configuration file, `interpreters` is reserved for the interpreter registry
+ */
+#[derive(Deserialize)]
+struct File {
+    #[serde(default)]
+    interpreters: Registry,
+
+    #[serde(flatten)]
+    entries: BTreeMap<String, Box<dyn Executor>>,
 }
 
 impl Config {
     // static methods
     pub fn load() -> Result<Config, failure::Error> {
         let path = Config::find_valid_path()?;
+        Config::load_from(&path)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Config, failure::Error> {
         if !path.exists() {
             return Ok(Config {
                 entries: BTreeMap::new(),
+                interpreters: Registry::default(),
+                path: path.to_owned(),
             });
         }
 
-        let toml_str = match fs::read_to_string(&path) {
-            Ok(string) => string,
-            Err(e) => {
-                let msg = format!("could not open the file {} because {}", path.display(), e);
-                return Err(failure::err_msg(msg));
-            }
-        };
-
-        let entries: BTreeMap<String, Box<dyn Executor>> = match toml::from_str(&toml_str) {
+        let toml_str = Config::read(path)?;
+        let file: File = match toml::from_str(&toml_str) {
             Ok(value) => value,
-            Err(e) => {
-                let msg = format!("could not parse configuration file because {}", e);
-                return Err(failure::err_msg(msg));
-            }
+            Err(e) => return Err(Error::config_parse(path, &e).into()),
         };
 
-        Ok(Config { entries })
+        Ok(Config {
+            entries: file.entries,
+            interpreters: file.interpreters,
+            path: path.to_owned(),
+        })
     }
 
-    fn find_valid_path() -> Result<PathBuf, failure::Error> {
-        // $CENTORIA_CONFIG_PATH
+    pub fn find_valid_path() -> Result<PathBuf, failure::Error> {
+        // explicitly specified path is used for both reading and creating
         if let Ok(path) = env::var("CENTORIA_CONFIG_PATH") {
-            let path = PathBuf::from(&path);
+            return Ok(PathBuf::from(&path));
+        }
 
-            if path.exists() {
-                return Ok(path);
-            }
+        let paths = Config::candidate_paths();
+
+        // first existing one, or fallback to $HOME/.centoria.toml
+        match paths.iter().find(|w| w.exists()).or_else(|| paths.last()) {
+            Some(path) => Ok(path.to_owned()),
+            None => Err(failure::err_msg("could not detect configuration path.")),
+        }
+    }
+
+    // all paths that centoria looks up, ordered by priority
+    pub fn candidate_paths() -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = vec![];
+
+        // $CENTORIA_CONFIG_PATH
+        if let Ok(path) = env::var("CENTORIA_CONFIG_PATH") {
+            paths.push(PathBuf::from(&path));
         }
 
         // $SYSTEM_CONFIGURATION_DIRECTORY/centoria/centoria.toml
         if let Some(path) = dirs::config_dir() {
-            let path: PathBuf = [&path.to_str().unwrap(), "centoria", "centoria.toml"]
+            let path: PathBuf = [path.to_str().unwrap(), "centoria", "centoria.toml"]
                 .iter()
                 .collect();
 
-            if path.exists() {
-                return Ok(path);
-            }
+            paths.push(path);
         }
 
         // $HOME/.centoria.toml
         if let Some(path) = dirs::home_dir() {
-            let path: PathBuf = [&path.to_str().unwrap(), ".centoria.toml"].iter().collect();
+            let path: PathBuf = [path.to_str().unwrap(), ".centoria.toml"].iter().collect();
+
+            paths.push(path);
+        }
+
+        paths
+    }
 
-            return Ok(path);
+    // read configuration without deserializing entries, for validation
+    pub fn load_raw(path: &Path) -> Result<toml::value::Table, failure::Error> {
+        let toml_str = Config::read(path)?;
+        match toml::from_str(&toml_str) {
+            Ok(value) => Ok(value),
+            Err(e) => Err(Error::config_parse(path, &e).into()),
         }
+    }
 
-        Err(failure::err_msg("could not detect configuration path."))
+    fn read(path: &Path) -> Result<String, Error> {
+        fs::read_to_string(path).map_err(|e| Error::Io {
+            path: path.to_owned(),
+            message: format!("could not open the file {} because {}", path.display(), e),
+        })
     }
 
     // instance methods
     pub fn add(&mut self, name: &str, executor: Box<dyn Executor>) -> Result<(), failure::Error> {
+        if name == "interpreters" {
+            return Err(failure::err_msg(
+                "`interpreters` is reserved for interpreter definitions",
+            ));
+        }
+
         if executor.is::<SubCommand>() {
             return self.add_child(name, executor.downcast::<SubCommand>().ok().unwrap());
         }
 
         if self.exists(name) {
-            let msg = format!("function `{}` is already exists", name);
-            return Err(failure::err_msg(msg));
+            let name = name.to_owned();
+            return Err(Error::AlreadyExists { name }.into());
         }
 
         self.entries.insert(name.to_string(), executor);
@@ -100,8 +151,8 @@ impl Config {
     pub fn remove(&mut self, name: &str, program: Option<&str>) -> Result<(), failure::Error> {
         if let Some(program) = program {
             if !self.exists(program) {
-                let msg = format!("function `{}` is not exists", program);
-                return Err(failure::err_msg(msg));
+                let name = program.to_owned();
+                return Err(Error::NotFound { name }.into());
             }
 
             let parent: &mut Box<dyn Executor> = self.entries.get_mut(program).unwrap();
@@ -116,29 +167,73 @@ impl Config {
         }
 
         if !self.exists(name) {
-            let msg = format!("function `{}` is not exists", name);
-            return Err(failure::err_msg(msg));
+            let name = name.to_owned();
+            return Err(Error::NotFound { name }.into());
         }
 
         self.entries.remove(name);
         Ok(())
     }
 
-    pub fn get(&self, name: &str) -> Option<&Box<dyn Executor>> {
-        self.entries.get(name)
+    pub fn get(&self, name: &str) -> Option<&dyn Executor> {
+        self.entries.get(name).map(|w| w.as_ref())
     }
 
     pub fn exists(&self, name: &str) -> bool {
-        self.entries.get(name).is_some()
+        self.entries.contains_key(name)
+    }
+
+    // interpreters defined in this configuration, used by its functions
+    pub fn interpreters(&self) -> &Registry {
+        &self.interpreters
     }
 
     pub fn keys(&self) -> Vec<String> {
         self.entries.keys().cloned().collect()
     }
 
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn execute(&self, request: &mut ExecutionRequest) -> Result<ExitStatus, failure::Error> {
+        let name = request.name.to_owned();
+        let executor = match self.get(&name) {
+            Some(value) => value,
+            None => return Err(Error::NotFound { name }.into()),
+        };
+
+        // help is available even if the function is not
+        if !executor.is_help(&request.args) {
+            if let Err(reason) = executor.can_execute(&self.interpreters) {
+                return Err(Error::Unavailable { name, reason }.into());
+            }
+        }
+
+        if request.config_dir.is_none() {
+            request.config_dir = self.path.parent().map(|w| w.to_owned());
+        }
+        request.interpreters = self.interpreters.to_owned();
+
+        executor.execute(request)
+    }
+
     pub fn save(&mut self) -> Result<(), failure::Error> {
-        let path = Config::find_valid_path()?;
+        #[derive(Serialize)]
+        struct Interpreters<'a> {
+            interpreters: &'a Registry,
+        }
+
+        // interpreters follow functions, both are tables so that they can be concatenated
+        let interpreters = Interpreters {
+            interpreters: &self.interpreters,
+        };
         let toml_str = match toml::to_string_pretty(&self.entries) {
+            Ok(value) if self.interpreters.is_empty() => Ok(value),
+            Ok(value) => toml::to_string_pretty(&interpreters).map(|w| format!("{}\n{}", value, w)),
+            Err(e) => Err(e),
+        };
+        let toml_str = match toml_str {
             Ok(value) => value,
             Err(e) => {
                 let msg = format!("could not save configuration because {}", e);
@@ -146,7 +241,31 @@ impl Config {
             }
         };
 
-        fs::write(path, toml_str)?;
+        if let Some(parent) = self.path.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                return Err(Error::Io {
+                    path: parent.to_owned(),
+                    message: format!(
+                        "could not create directory {} because {}",
+                        parent.display(),
+                        e
+                    ),
+                }
+                .into());
+            }
+        }
+
+        if let Err(e) = fs::write(&self.path, toml_str) {
+            return Err(Error::Io {
+                path: self.path.to_owned(),
+                message: format!(
+                    "could not write configuration to {} because {}",
+                    self.path.display(),
+                    e
+                ),
+            }
+            .into());
+        }
 
         Ok(())
     }
@@ -0,0 +1,311 @@
+use std::error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+use crate::shell::Unavailable;
+
+// exit codes of `cet`, following sysexits.h so that they rarely overlap ones of functions
+pub const EXIT_ARGUMENT: i32 = 64; // EX_USAGE
+pub const EXIT_TEMPLATE: i32 = 65; // EX_DATAERR
+pub const EXIT_UNSUPPORTED_SHELL: i32 = 69; // EX_UNAVAILABLE
+pub const EXIT_FAILURE: i32 = 70; // EX_SOFTWARE
+pub const EXIT_ALREADY_EXISTS: i32 = 73; // EX_CANTCREAT
+pub const EXIT_IO: i32 = 74; // EX_IOERR
+pub const EXIT_CONFIG: i32 = 78; // EX_CONFIG
+pub const EXIT_UNAVAILABLE: i32 = 126;
+pub const EXIT_NOT_FOUND: i32 = 127;
+pub const EXIT_SIGNAL_BASE: i32 = 128;
+
+/**
+ * errors that should be distinguished by callers
+ */
+#[derive(Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Error {
+    // function is not defined in configuration
+    NotFound {
+        name: String,
+    },
+
+    // sub-function is not defined in the subcommand
+    SubFunctionNotFound {
+        name: String,
+    },
+
+    // function is already defined in configuration
+    AlreadyExists {
+        name: String,
+    },
+
+    // sub-function is already defined in the subcommand
+    SubFunctionAlreadyExists {
+        name: String,
+    },
+
+    // shell is missing or condition failed
+    Unavailable {
+        name: String,
+        reason: Unavailable,
+    },
+
+    // required argument is not given (or given range is empty)
+    ArgumentMissing {
+        placeholder: String, // e.g. `{1}`
+        description: Option<String>,
+        given: usize,
+        required: usize,
+        usage: Option<String>, // e.g. `show-err-logs <application name>`
+    },
+
+    // given argument does not satisfy the type of the parameter
+    ArgumentInvalid {
+        placeholder: String,
+        value: String,
+        expected: String, // e.g. `int` or `one of start|stop`
+    },
+
+    // option is not declared in `options` of the function
+    UnknownOption {
+        option: String,
+        known: Vec<String>,
+    },
+
+    // option takes a value, but it is not given
+    OptionValueMissing {
+        option: String,
+    },
+
+    // configuration file is not valid TOML or contains unknown entries, `line` is 1-origin
+    ConfigParse {
+        path: PathBuf,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+
+    // configuration file contains problems found by `cet check`
+    ConfigInvalid {
+        problems: usize,
+    },
+
+    // placeholders in the command could not be parsed
+    Template {
+        command: String,
+        message: String,
+    },
+
+    // built-in placeholder could not be resolved, e.g. `{git_root}` outside of repository
+    Placeholder {
+        placeholder: String,
+        message: String,
+    },
+
+    UnsupportedShell {
+        shell: String,
+    },
+
+    // could not read or write the file
+    Io {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl Error {
+    pub fn config_parse(path: &Path, e: &toml::de::Error) -> Error {
+        let position = e.line_col();
+
+        Error::ConfigParse {
+            path: path.to_owned(),
+            line: position.map(|(line, _)| line + 1),
+            column: position.map(|(_, column)| column + 1),
+            message: e.to_string(),
+        }
+    }
+
+    // attaches the usage line of the function to argument errors
+    pub fn with_usage(self, line: String) -> Error {
+        match self {
+            Error::ArgumentMissing {
+                placeholder,
+                description,
+                given,
+                required,
+                ..
+            } => Error::ArgumentMissing {
+                placeholder,
+                description,
+                given,
+                required,
+                usage: Some(line),
+            },
+            _ => self,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::NotFound { .. } | Error::SubFunctionNotFound { .. } => EXIT_NOT_FOUND,
+            Error::AlreadyExists { .. } | Error::SubFunctionAlreadyExists { .. } => {
+                EXIT_ALREADY_EXISTS
+            }
+            Error::Unavailable { .. } => EXIT_UNAVAILABLE,
+            Error::ArgumentMissing { .. }
+            | Error::ArgumentInvalid { .. }
+            | Error::UnknownOption { .. }
+            | Error::OptionValueMissing { .. } => EXIT_ARGUMENT,
+            Error::ConfigParse { .. } | Error::ConfigInvalid { .. } => EXIT_CONFIG,
+            Error::Template { .. } | Error::Placeholder { .. } => EXIT_TEMPLATE,
+            Error::UnsupportedShell { .. } => EXIT_UNSUPPORTED_SHELL,
+            Error::Io { .. } => EXIT_IO,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NotFound { name } => write!(f, "function `{}` is not exists", name),
+            Error::SubFunctionNotFound { name } => {
+                write!(f, "sub-function `{}` is not exists in this function", name)
+            }
+            Error::AlreadyExists { name } => write!(f, "function `{}` is already exists", name),
+            Error::SubFunctionAlreadyExists { name } => write!(
+                f,
+                "sub-function `{}` is already exists in this function",
+                name
+            ),
+            Error::Unavailable { name, reason } => {
+                write!(f, "could not execute the function `{}`: {}", name, reason)
+            }
+            Error::ArgumentMissing {
+                placeholder,
+                description,
+                given,
+                required,
+                usage,
+            } => {
+                write!(f, "missing argument {}", placeholder)?;
+                if let Some(description) = description {
+                    write!(f, " ({})", description)?;
+                }
+                write!(f, ": {} given, but {} required", given, required)?;
+
+                match usage {
+                    Some(usage) => write!(f, "\nusage: {}", usage),
+                    None => write!(f, ", please use `show` subcommand for checking parameters"),
+                }
+            }
+            Error::ArgumentInvalid {
+                placeholder,
+                value,
+                expected,
+            } => write!(
+                f,
+                "invalid argument `{}` for {}: expected {}",
+                value, placeholder, expected
+            ),
+            Error::UnknownOption { option, known } => write!(
+                f,
+                "unknown option `{}`, available options are: {}",
+                option,
+                known.join(", ")
+            ),
+            Error::OptionValueMissing { option } => {
+                write!(f, "option `{}` requires a value", option)
+            }
+            Error::ConfigParse { path, message, .. } => write!(
+                f,
+                "could not parse configuration file {} because {}",
+                path.display(),
+                message
+            ),
+            Error::ConfigInvalid { problems } => {
+                write!(f, "found {} problem(s) in configuration", problems)
+            }
+            Error::Template { message, .. } => write!(f, "{}", message),
+            Error::Placeholder {
+                placeholder,
+                message,
+            } => write!(f, "could not resolve {}: {}", placeholder, message),
+            Error::UnsupportedShell { shell } => write!(f, "not supported shell: {}", shell),
+            Error::Io { message, .. } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+// exit code of `cet` for the given error, falling back to EXIT_FAILURE
+pub fn exit_code(error: &failure::Error) -> i32 {
+    error
+        .downcast_ref::<Error>()
+        .map_or(EXIT_FAILURE, |w| w.exit_code())
+}
+
+// exit code of `cet exec` for the status of the function, following the `128 + signal` convention of shells
+pub fn status_code(status: &ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return EXIT_SIGNAL_BASE + signal;
+        }
+    }
+
+    status.code().unwrap_or(EXIT_FAILURE)
+}
+
+// `{"kind": "...", "code": N, "message": "...", ...fields}` for `--error-format json`
+pub fn to_json(error: &failure::Error) -> serde_json::Value {
+    let mut value = match error.downcast_ref::<Error>() {
+        Some(error) => serde_json::to_value(error).unwrap_or_default(),
+        None => serde_json::json!({ "kind": "other" }),
+    };
+
+    if let Some(object) = value.as_object_mut() {
+        object.insert("code".to_owned(), exit_code(error).into());
+        object.insert("message".to_owned(), error.to_string().into());
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use crate::shell::Unavailable;
+
+    #[test]
+    fn exit_code() {
+        let error: failure::Error = Error::NotFound {
+            name: "search".to_owned(),
+        }
+        .into();
+        assert_eq!(super::exit_code(&error), 127);
+        assert_eq!(super::exit_code(&failure::err_msg("unknown")), 70);
+    }
+
+    #[test]
+    fn to_json() {
+        let error: failure::Error = Error::Unavailable {
+            name: "search".to_owned(),
+            reason: Unavailable::ConditionFailed {
+                condition: "which rg".to_owned(),
+                code: Some(1),
+                stderr: "".to_owned(),
+            },
+        }
+        .into();
+
+        assert_eq!(
+            super::to_json(&error).to_string(),
+            r#"{"code":126,"kind":"unavailable","message":"could not execute the function `search`: condition `which rg` exited 1","name":"search","reason":{"code":1,"condition":"which rg","kind":"condition_failed","stderr":""}}"#
+        );
+        assert_eq!(
+            super::to_json(&failure::err_msg("broken")).to_string(),
+            r#"{"code":70,"kind":"other","message":"broken"}"#
+        );
+    }
+}
@@ -1,12 +1,16 @@
 use std::io::Write;
-use std::process::{Command, ExitStatus};
+use std::path::Path;
+use std::process::ExitStatus;
 
-use clap::ArgMatches;
-use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+use termcolor::Color;
 
-use crate::executors::Executor;
+use crate::cache;
+use crate::executors::{validate, DisplayRequest, ExecutionRequest, Executor, Issue};
+use crate::fmt;
+use crate::interpreter::Registry;
 use crate::pad;
 use crate::shell;
+use crate::shell::Unavailable;
 
 /**
  * alias works as shell aliases
@@ -19,6 +23,9 @@ pub struct Alias {
     #[serde(skip_serializing_if = "Option::is_none")]
     condition: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    condition_ttl: Option<String>, // e.g. `1h`, cache the result of condition
+
     #[serde(skip_serializing_if = "Option::is_none")]
     cwd: Option<String>,
 
@@ -45,6 +52,7 @@ impl Alias {
         Alias {
             command: command.to_owned(),
             condition,
+            condition_ttl: None,
             cwd,
             description,
             shell,
@@ -53,97 +61,85 @@ impl Alias {
 
     fn shell(&self) -> &str {
         match &self.shell {
-            Some(shell) => &shell,
+            Some(shell) => shell,
             None => "sh",
         }
     }
-
-    fn format_args(&self, arg: &str) -> Result<String, ()> {
-        if arg.contains(' ') {
-            Ok(format!("\"{}\"", arg))
-        } else {
-            Ok(arg.to_owned())
-        }
-    }
 }
 
 #[typetag::serde(name = "alias")]
 impl Executor for Alias {
-    fn can_execute(&self) -> bool {
-        if self.shell() != "sh" {
-            match Command::new(self.shell()).arg("--version").output() {
-                Ok(_) => {}
-                Err(_) => return false,
-            };
-        }
-
-        if let Some(condition) = &self.condition {
-            #[rustfmt::skip]
-            return match Command::new(self.shell()).args(&["-c", &condition]).output() {
-                Ok(value) => value.status.success(),
-                Err(_) => false
-            };
-        }
-
-        true
+    fn can_execute(&self, interpreters: &Registry) -> Result<(), Unavailable> {
+        cache::probe(
+            interpreters,
+            self.shell(),
+            &self.condition,
+            &self.condition_ttl,
+        )
     }
 
-    fn execute(&self, args: &ArgMatches) -> Result<ExitStatus, failure::Error> {
-        let extra: Option<Vec<String>> = args
-            .values_of("extra")
-            .map(|w| w.map(|v| self.format_args(v).unwrap()).collect());
-        let show_verbose = args.is_present("verbose");
+    fn execute(&self, request: &mut ExecutionRequest) -> Result<ExitStatus, failure::Error> {
+        // extra arguments are quoted by the rule of the interpreter
+        let quoting = request.interpreters.get(self.shell())?.quoting();
+        let extra: Vec<String> = request.args.iter().map(|v| quoting.quote(v)).collect();
 
         let mut execute = self.command.to_string();
-        if let Some(extra) = extra {
+        if !extra.is_empty() {
             execute.push_str(&format!(" {}", extra.join(" ")));
         }
 
-        if show_verbose {
-            let mut stdout = StandardStream::stdout(ColorChoice::Always);
-            let mut clrspc = ColorSpec::new();
-            clrspc.set_bold(true).set_fg(Some(Color::Green));
-            stdout.set_color(&clrspc)?;
-            write!(&mut stdout, "Executing")?;
-
-            clrspc.set_bold(false).set_fg(None);
-            stdout.set_color(&clrspc)?;
-            writeln!(&mut stdout, ": {}", execute.replace("\n", ""))?;
-            stdout.flush()?;
+        if request.verbose {
+            let output = request.output.as_mut();
+            fmt::write_label(output, "Executing", Color::Green, &execute)?;
         }
 
-        let cwd = match &self.cwd {
-            Some(value) => Some(value.to_string()),
-            None => None,
-        };
+        if request.dry_run {
+            let output = request.output.as_mut();
+            fmt::write_label(output, "dry-run", Color::Blue, &execute)?;
+            return Err(failure::err_msg("executed as a dry run"));
+        }
+
+        let cwd = shell::working_dir(&self.cwd, &request.config_dir)?;
 
-        shell::safe_run(self.shell(), execute.trim(), cwd)
+        shell::safe_run(
+            request.backend.as_mut(),
+            &request.interpreters,
+            self.shell(),
+            execute.trim(),
+            cwd.as_deref(),
+        )
     }
 
-    fn display(&self, args: &ArgMatches) -> Result<(), failure::Error> {
-        let name = args.value_of("name").unwrap();
+    fn display(&self, request: &mut DisplayRequest) -> Result<(), failure::Error> {
         let description = match &self.description {
             Some(value) => value,
             None => "No description provided",
         };
 
-        println!(
+        writeln!(
+            request.output,
             "\
 Usage (Cet)    : cet exec {name} -- <EXTRA ARGS>
 Usage (Direct) : {name} <EXTRA ARGS>
 Execute        : {command}
 Shell          : {shell}
+Status         : {status}
 
 {description}",
-            name = name,
+            name = request.name,
             description = description.trim(),
             command = pad::left_pad_without_1st(&self.command, 17),
             shell = self.shell(),
-        );
+            status = fmt::to_status(&self.can_execute(&request.interpreters)),
+        )?;
 
         Ok(())
     }
 
+    fn complete(&self, _args: &[String]) -> Vec<String> {
+        vec![]
+    }
+
     fn export_as(&self, name: &str) -> Result<String, failure::Error> {
         Ok(format!(
             "alias {name}='cet exec {name} -- '",
@@ -157,4 +153,80 @@ Shell          : {shell}
             None => "No description provided",
         }
     }
+
+    fn validate(&self, interpreters: &Registry, config_dir: &Path) -> Vec<Issue> {
+        let mut issues = vec![];
+        issues.extend(validate::shell(interpreters, self.shell()));
+        issues.extend(validate::ttl(&self.condition_ttl));
+        issues.extend(validate::cwd(&self.cwd, config_dir));
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use termcolor::Buffer;
+
+    use super::Alias;
+    use crate::executors::{DisplayRequest, ExecutionRequest, Executor};
+
+    fn execute(alias: &Alias, request: ExecutionRequest) -> (Result<i32, String>, String) {
+        let mut buffer = Buffer::no_color();
+        let result = alias
+            .execute(&mut request.output(&mut buffer))
+            .map(|w| w.code().unwrap())
+            .map_err(|e| e.to_string());
+
+        (result, String::from_utf8(buffer.into_inner()).unwrap())
+    }
+
+    #[test]
+    fn execute_as_dry_run() {
+        let alias = Alias::new("ls -al", None, None, None, None);
+        let args = vec!["foo".to_owned(), "bar baz".to_owned()];
+        let request = ExecutionRequest::new("la", args).dry_run(true);
+
+        let (result, output) = execute(&alias, request);
+        assert_eq!(result.unwrap_err(), "executed as a dry run");
+        assert_eq!(output, "dry-run: ls -al foo 'bar baz'\n");
+
+        let args = vec!["it's".to_owned(), "$HOME".to_owned(), "a b".to_owned()];
+        let request = ExecutionRequest::new("la", args).dry_run(true);
+
+        let (_, output) = execute(&alias, request);
+        assert_eq!(output, "dry-run: ls -al 'it'\\''s' '$HOME' 'a b'\n");
+    }
+
+    #[test]
+    fn execute_with_verbose() {
+        let alias = Alias::new("exit", None, None, None, None);
+        let request = ExecutionRequest::new("quit", vec!["3".to_owned()]).verbose(true);
+
+        let (result, output) = execute(&alias, request);
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(output, "Executing: exit 3\n");
+    }
+
+    #[test]
+    fn display() {
+        let alias = Alias::new("rg", None, None, Some("search text"), None);
+        let mut buffer = Buffer::no_color();
+        alias
+            .display(&mut DisplayRequest::new("search", None).output(&mut buffer))
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(buffer.into_inner()).unwrap(),
+            "\
+Usage (Cet)    : cet exec search -- <EXTRA ARGS>
+Usage (Direct) : search <EXTRA ARGS>
+Execute        : rg
+Shell          : sh
+Status         : available
+
+search text
+"
+        );
+    }
 }
@@ -1,14 +1,19 @@
 use std::io::Write;
-use std::process::{Command, ExitStatus};
+use std::path::Path;
+use std::process::ExitStatus;
 
-use clap::ArgMatches;
-use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+use termcolor::Color;
 
-use crate::argparse::ArgParser;
-use crate::executors::Executor;
+use crate::argparse::{self, Annotations, ArgParser};
+use crate::cache;
+use crate::executors::{self, prompt, validate, DisplayRequest, ExecutionRequest, Executor, Issue};
 use crate::fmt;
+use crate::interpreter::Registry;
+use crate::options::{self, Options};
 use crate::pad;
+use crate::placeholders;
 use crate::shell;
+use crate::shell::Unavailable;
 
 /**
  * function works as shell functions
@@ -20,6 +25,9 @@ pub struct Function {
     #[serde(skip_serializing_if = "Option::is_none")]
     condition: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    condition_ttl: Option<String>, // e.g. `1h`, cache the result of condition
+
     #[serde(skip_serializing_if = "Option::is_none")]
     cwd: Option<String>,
 
@@ -30,7 +38,19 @@ pub struct Function {
     shell: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    descriptions: Option<Vec<String>>, // description for arguments
+    descriptions: Option<Annotations>, // description for arguments
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    types: Option<Annotations>, // type of arguments, e.g. `int` or `enum:start|stop`
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_flags: Option<Vec<String>>, // defaults to `--help` and `-h`
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt: Option<bool>, // prompt for missing arguments on terminal
+
+    #[serde(default, skip_serializing_if = "Options::is_empty")]
+    options: Options, // named options, substituted via `{opt:name}`
 }
 
 impl Function {
@@ -49,102 +69,101 @@ impl Function {
         Function {
             command: command.to_owned(),
             condition,
+            condition_ttl: None,
             cwd,
             description,
             descriptions: None,
+            types: None,
+            help_flags: None,
+            prompt: None,
+            options: Options::default(),
             shell,
         }
     }
 
+    // whether the command has placeholders to be substituted, so that it needs a function,
+    // escaped placeholders such as `{{0}}` also match, they are unescaped only by functions
+    pub fn is_template(command: &str) -> bool {
+        argparse::variable().is_match(command)
+            || placeholders::contains(command)
+            || !options::placeholders(command).is_empty()
+    }
+
     fn shell(&self) -> &str {
         match &self.shell {
-            Some(shell) => &shell,
+            Some(shell) => shell,
             None => "sh",
         }
     }
-
-    fn format_args(&self, arg: &str) -> Result<String, ()> {
-        if arg.contains(' ') {
-            Ok(format!("\"{}\"", arg))
-        } else {
-            Ok(arg.to_owned())
-        }
-    }
 }
 
 #[typetag::serde(name = "function")]
 impl Executor for Function {
-    fn can_execute(&self) -> bool {
-        if self.shell() != "sh" {
-            match Command::new(self.shell()).arg("--version").output() {
-                Ok(_) => {}
-                Err(_) => return false,
-            };
-        }
+    fn can_execute(&self, interpreters: &Registry) -> Result<(), Unavailable> {
+        cache::probe(
+            interpreters,
+            self.shell(),
+            &self.condition,
+            &self.condition_ttl,
+        )
+    }
 
-        if let Some(condition) = &self.condition {
-            #[rustfmt::skip]
-            return match Command::new(self.shell()).args(&["-c", &condition]).output() {
-                Ok(value) => value.status.success(),
-                Err(_) => false
-            };
+    fn execute(&self, request: &mut ExecutionRequest) -> Result<ExitStatus, failure::Error> {
+        if executors::is_help(&self.help_flags, request.args.first()) {
+            let output = request.output.as_mut();
+            self.display(
+                &mut DisplayRequest::new(&request.name, None)
+                    .interpreters(&request.interpreters)
+                    .output(output),
+            )?;
+            return Ok(ExitStatus::default());
         }
 
-        true
-    }
+        let (values, mut positionals) = self.options.parse(&request.args)?;
+        let quoting = request.interpreters.get(self.shell())?.quoting();
 
-    fn execute(&self, args: &ArgMatches) -> Result<ExitStatus, failure::Error> {
-        let extra: Vec<String> = args.values_of("extra").map_or_else(
-            || vec![],
-            |w| w.map(|v| self.format_args(v).unwrap()).collect(),
-        );
-        let show_verbose = args.is_present("verbose");
-
-        // building
-        let mut parser = ArgParser::new(&self.command, None);
+        // building, options are substituted first and arguments are quoted by parser after type checking
+        let command = self.options.substitute(&self.command, &values, quoting);
+        let mut parser = ArgParser::new(&command, self.descriptions.as_ref())
+            .types(self.types.as_ref())
+            .quoting(quoting)
+            .context(request.context());
         parser.parse()?;
 
-        let execute = match parser.fill(extra) {
-            Ok(value) => value,
-            Err(e) => {
-                let msg = format!(
-                    "{}, please use `show` subcommand for checking parameters",
-                    e
-                );
-                return Err(failure::err_msg(msg));
-            }
-        };
+        let enabled = self.prompt.unwrap_or(false);
+        prompt::missing(enabled, &parser, &mut positionals, request)?;
 
-        if show_verbose {
-            let mut stdout = StandardStream::stdout(ColorChoice::Always);
-            let mut clrspc = ColorSpec::new();
-            clrspc.set_bold(true).set_fg(Some(Color::Green));
-            stdout.set_color(&clrspc)?;
-            write!(&mut stdout, "Executing")?;
-
-            clrspc.set_bold(false).set_fg(None);
-            stdout.set_color(&clrspc)?;
-            writeln!(&mut stdout, ": {}", execute.replace("\n", ""))?;
-            stdout.flush()?;
+        let execute = parser
+            .fill(positionals)
+            .map_err(|e| e.with_usage(parser.usage(&request.name)))?;
+
+        if request.verbose {
+            let output = request.output.as_mut();
+            fmt::write_label(output, "Executing", Color::Green, &execute)?;
         }
 
-        let cwd = match &self.cwd {
-            Some(value) => Some(value.to_string()),
-            None => None,
-        };
+        if request.dry_run {
+            let output = request.output.as_mut();
+            fmt::write_label(output, "dry-run", Color::Blue, &execute)?;
+            return Err(failure::err_msg("executed as a dry run"));
+        }
 
-        shell::safe_run(self.shell(), execute.trim(), cwd)
+        let cwd = shell::working_dir(&self.cwd, &request.config_dir)?;
+
+        shell::safe_run(
+            request.backend.as_mut(),
+            &request.interpreters,
+            self.shell(),
+            execute.trim(),
+            cwd.as_deref(),
+        )
     }
 
-    fn display(&self, args: &ArgMatches) -> Result<(), failure::Error> {
-        let descriptions = match &self.descriptions {
-            Some(values) => Some(values.iter().map(|s| s.as_str()).collect()),
-            None => None,
-        };
-        let mut parser = ArgParser::new(&self.command, descriptions);
+    fn display(&self, request: &mut DisplayRequest) -> Result<(), failure::Error> {
+        let mut parser =
+            ArgParser::new(&self.command, self.descriptions.as_ref()).types(self.types.as_ref());
         parser.parse()?;
 
-        let name = args.value_of("name").unwrap();
         let description = match &self.description {
             Some(value) => fmt::to_single_line(value),
             None => "No description provided".to_owned(),
@@ -152,30 +171,36 @@ impl Executor for Function {
         let parameters = match parser.arguments() {
             Some(values) => values
                 .iter()
-                .enumerate()
-                .map(|(i, w)| {
-                    format!(
-                        "{index} ({opt}): {description}",
-                        index = i,
-                        opt = w.attribute(),
-                        description = fmt::to_single_line(w.description())
-                    )
-                })
+                .map(fmt::to_parameter)
                 .collect::<Vec<String>>(),
             None => vec!["No description provided".to_owned()],
         };
+        let options = match self.options.describe() {
+            values if values.is_empty() => "".to_owned(),
+            values => format!(
+                "\nOptions        :\n{}",
+                values
+                    .iter()
+                    .map(|w| format!("    {}", w))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ),
+        };
 
-        println!(
+        writeln!(
+            request.output,
             "\
 Usage (Cet)    : cet exec {name} -- <EXTRA ARGS>
 Usage (Direct) : {name} <EXTRA ARGS>
 Execute        : {command}
 Shell          : {shell}
+Status         : {status}
 Parameters     :
-{parameters}
+{parameters}{options}
 
 {description}",
-            name = name,
+            name = request.name,
+            options = options,
             parameters = parameters
                 .iter()
                 .map(|w| format!("    {}", w))
@@ -184,11 +209,32 @@ Parameters     :
             description = description.trim(),
             command = pad::left_pad_without_1st(&self.command, 17),
             shell = self.shell(),
-        );
+            status = fmt::to_status(&self.can_execute(&request.interpreters)),
+        )?;
 
         Ok(())
     }
 
+    fn complete(&self, args: &[String]) -> Vec<String> {
+        let current = args.last().map_or("", |s| s.as_str());
+        if current.starts_with('-') && !self.options.is_empty() {
+            return self.options.flags();
+        }
+
+        // the last argument is being completed, so it is excluded from parsing
+        let previous = &args[..args.len().saturating_sub(1)];
+        let index = match self.options.parse(previous) {
+            Ok((_, positionals)) => positionals.len(),
+            Err(_) => return vec![],
+        };
+
+        let mut parser = ArgParser::new(&self.command, None).types(self.types.as_ref());
+        match parser.parse() {
+            Ok(_) => parser.completions(index),
+            Err(_) => vec![],
+        }
+    }
+
     fn export_as(&self, name: &str) -> Result<String, failure::Error> {
         Ok(format!(
             "alias {name}='cet exec {name} -- '",
@@ -202,4 +248,171 @@ Parameters     :
             None => "No description provided",
         }
     }
+
+    fn is_help(&self, args: &[String]) -> bool {
+        executors::is_help(&self.help_flags, args.first())
+    }
+
+    fn validate(&self, interpreters: &Registry, config_dir: &Path) -> Vec<Issue> {
+        let mut issues = vec![];
+        issues.extend(validate::shell(interpreters, self.shell()));
+        issues.extend(validate::ttl(&self.condition_ttl));
+        issues.extend(validate::cwd(&self.cwd, config_dir));
+        issues.extend(validate::template("", &self.command, &self.descriptions));
+        issues.extend(validate::types("", &self.types));
+        issues.extend(validate::options(&self.command, &self.options));
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use termcolor::Buffer;
+
+    use super::{Annotations, Function};
+    use crate::executors::{DisplayRequest, ExecutionRequest, Executor};
+
+    fn execute(function: &Function, request: ExecutionRequest) -> (Result<i32, String>, String) {
+        let mut buffer = Buffer::no_color();
+        let result = function
+            .execute(&mut request.output(&mut buffer))
+            .map(|w| w.code().unwrap())
+            .map_err(|e| e.to_string());
+
+        (result, String::from_utf8(buffer.into_inner()).unwrap())
+    }
+
+    #[test]
+    fn is_template() {
+        let templates = [
+            "tail {0}",
+            "tar -cf {0} {1..|join:,}",
+            "gcc {0..|each:-I %}",
+            "ls {0?}",
+            "ls {-1?}",
+            "cp {..-1|join:,} {-1}",
+            "tail {opt:lines}",
+            "vi {env:EDITOR}",
+            "echo {{0}}",
+        ];
+        for template in &templates {
+            assert!(Function::is_template(template), "{}", template);
+        }
+
+        assert!(!Function::is_template("awk '{print $1}'"));
+        assert!(!Function::is_template("ls -al"));
+    }
+
+    #[test]
+    fn execute_as_dry_run() {
+        let function = Function::new("tail -f /var/log/{0}/error.log", None, None, None, None);
+        let request = ExecutionRequest::new("logs", vec!["nginx".to_owned()]).dry_run(true);
+
+        let (result, output) = execute(&function, request);
+        assert_eq!(result.unwrap_err(), "executed as a dry run");
+        assert_eq!(output, "dry-run: tail -f /var/log/nginx/error.log\n");
+    }
+
+    #[test]
+    fn execute_with_verbose() {
+        let function = Function::new("exit {1}", None, None, None, None);
+        let args = vec!["1".to_owned(), "4".to_owned()];
+        let request = ExecutionRequest::new("quit", args).verbose(true);
+
+        let (result, output) = execute(&function, request);
+        assert_eq!(result.unwrap(), 4);
+        assert_eq!(output, "Executing: exit 4\n");
+    }
+
+    #[test]
+    fn execute_without_required_arguments() {
+        let mut function = Function::new("tail -f /var/log/{0}/error.log", None, None, None, None);
+        function.descriptions = Some(Annotations::List(vec!["application name".to_owned()]));
+        let request = ExecutionRequest::new("logs", vec![]).dry_run(true);
+
+        let (result, output) = execute(&function, request);
+        assert_eq!(
+            result.unwrap_err(),
+            "missing argument {0} (application name): 0 given, but 1 required\nusage: logs <application name>"
+        );
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn execute_help() {
+        let mut function = Function::new("tail -f /var/log/{0}/error.log", None, None, None, None);
+        let request = ExecutionRequest::new("logs", vec!["-h".to_owned()]);
+
+        let (result, output) = execute(&function, request);
+        assert_eq!(result.unwrap(), 0);
+        assert!(output.starts_with("Usage (Cet)    : cet exec logs -- <EXTRA ARGS>\n"));
+
+        // `help_flags` replaces reserved flags
+        function.help_flags = Some(vec!["--usage".to_owned()]);
+        let request = ExecutionRequest::new("logs", vec!["-h".to_owned()]).dry_run(true);
+
+        let (_, output) = execute(&function, request);
+        assert_eq!(output, "dry-run: tail -f /var/log/-h/error.log\n");
+    }
+
+    #[test]
+    fn execute_with_options() {
+        let mut function = Function::new(
+            "tail {opt:follow} -n {opt:lines} {0}",
+            None,
+            None,
+            None,
+            None,
+        );
+        function.options = toml::from_str(
+            "follow = { short = 'f' }\nlines = { short = 'n', value = true, default = '10' }",
+        )
+        .unwrap();
+
+        let args = vec!["-f".to_owned(), "app.log".to_owned()];
+        let request = ExecutionRequest::new("logs", args).dry_run(true);
+        let (_, output) = execute(&function, request);
+        assert_eq!(output, "dry-run: tail --follow -n 10 app.log\n");
+
+        // combined short options, and arguments are never taken as placeholders
+        let args = vec!["-fn5".to_owned(), "{opt:follow}".to_owned()];
+        let request = ExecutionRequest::new("logs", args).dry_run(true);
+        let (_, output) = execute(&function, request);
+        assert_eq!(output, "dry-run: tail --follow -n 5 '{opt:follow}'\n");
+
+        let args = vec!["-x".to_owned(), "app.log".to_owned()];
+        let request = ExecutionRequest::new("logs", args).dry_run(true);
+        let (result, _) = execute(&function, request);
+        assert_eq!(
+            result.unwrap_err(),
+            "unknown option `-x`, available options are: -f, --follow, -n, --lines"
+        );
+    }
+
+    #[test]
+    fn display() {
+        let mut function = Function::new("tail -f /var/log/{0}/error.log", None, None, None, None);
+        function.descriptions = Some(Annotations::List(vec!["application name".to_owned()]));
+
+        let mut buffer = Buffer::no_color();
+        function
+            .display(&mut DisplayRequest::new("logs", None).output(&mut buffer))
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(buffer.into_inner()).unwrap(),
+            "\
+Usage (Cet)    : cet exec logs -- <EXTRA ARGS>
+Usage (Direct) : logs <EXTRA ARGS>
+Execute        : tail -f /var/log/{0}/error.log
+Shell          : sh
+Status         : available
+Parameters     :
+    0 (required): application name
+
+No description provided
+"
+        );
+    }
 }
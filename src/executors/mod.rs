@@ -1,23 +1,82 @@
+use std::path::Path;
 use std::process::ExitStatus;
 
-use clap::ArgMatches;
 use downcast_rs::Downcast;
 
+use crate::interpreter::Registry;
+use crate::shell::Unavailable;
+
 mod alias;
 mod function;
+mod prompt;
+mod request;
+mod script;
 mod subcommand;
+mod validate;
 
 pub use alias::Alias;
 pub use function::Function;
+pub use request::{DisplayRequest, ExecutionRequest};
+pub use script::Script;
 pub use subcommand::SubCommand;
 
+/**
+ * problem found in the configuration of an executor
+ */
+#[derive(Debug)]
+pub struct Issue {
+    pub key: String, // relative key path, e.g. `cwd` or `subcommands.c.command`
+    pub message: String,
+    pub is_warning: bool, // reported, but not counted as a problem
+}
+
+impl Issue {
+    pub fn new(key: &str, message: &str) -> Issue {
+        Issue {
+            key: key.to_owned(),
+            message: message.to_owned(),
+            is_warning: false,
+        }
+    }
+
+    pub fn warning(key: &str, message: &str) -> Issue {
+        Issue {
+            is_warning: true,
+            ..Issue::new(key, message)
+        }
+    }
+}
+
+// reserved flags that show the details of a function instead of executing it
+const HELP_FLAGS: [&str; 2] = ["--help", "-h"];
+
+// `help_flags` overrides the reserved flags, and an empty list disables them
+fn is_help(help_flags: &Option<Vec<String>>, arg: Option<&String>) -> bool {
+    let arg = match arg {
+        Some(value) => value.as_str(),
+        None => return false,
+    };
+
+    match help_flags {
+        Some(values) => values.iter().any(|w| w == arg),
+        None => HELP_FLAGS.contains(&arg),
+    }
+}
+
 #[typetag::serde(tag = "runas")]
-pub trait Executor: Downcast {
-    fn can_execute(&self) -> bool;
+pub trait Executor: Downcast + Sync {
+    fn can_execute(&self, interpreters: &Registry) -> Result<(), Unavailable>;
+    fn complete(&self, args: &[String]) -> Vec<String>; // candidates for the last argument
     fn description(&self) -> &str;
-    fn display(&self, args: &ArgMatches) -> Result<(), failure::Error>;
-    fn execute(&self, args: &ArgMatches) -> Result<ExitStatus, failure::Error>;
+    fn display(&self, request: &mut DisplayRequest) -> Result<(), failure::Error>;
+    fn execute(&self, request: &mut ExecutionRequest) -> Result<ExitStatus, failure::Error>;
     fn export_as(&self, name: &str) -> Result<String, failure::Error>;
+    fn validate(&self, interpreters: &Registry, config_dir: &Path) -> Vec<Issue>; // relative to `config_dir`
+
+    // help is displayed without checking availability, executors without it return false
+    fn is_help(&self, _args: &[String]) -> bool {
+        false
+    }
 }
 
 impl_downcast!(Executor);
@@ -0,0 +1,99 @@
+use std::io::{self, BufRead, Write};
+
+use crate::argparse::ArgParser;
+use crate::executors::ExecutionRequest;
+
+// asks for missing required arguments and appends the answers to `args`,
+// does nothing unless prompting is enabled and the input is a terminal
+pub fn missing(
+    enabled: bool,
+    parser: &ArgParser,
+    args: &mut Vec<String>,
+    request: &mut ExecutionRequest,
+) -> io::Result<()> {
+    if !(enabled || request.prompt) || !request.terminal {
+        return Ok(());
+    }
+
+    for index in args.len()..parser.required_count() {
+        let argument = match parser.argument_at(index) {
+            Some(value) => value,
+            None => continue,
+        };
+
+        let mut message = format!("{} ({})", argument.label(), argument.description());
+        if let Some(kind) = argument.kind() {
+            message.push_str(&format!(" [{}]", kind));
+        }
+
+        match ask(&message, request)? {
+            Some(answer) => args.push(answer),
+            None => break, // EOF, missing arguments are reported by parser
+        }
+    }
+
+    Ok(())
+}
+
+fn ask(message: &str, request: &mut ExecutionRequest) -> io::Result<Option<String>> {
+    loop {
+        write!(request.prompts, "{}: ", message)?;
+        request.prompts.flush()?;
+
+        let mut line = String::new();
+        if request.input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let answer = line.trim_end_matches(&['\r', '\n'][..]);
+        if !answer.is_empty() {
+            return Ok(Some(answer.to_owned()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::argparse::{Annotations, ArgParser};
+    use crate::executors::ExecutionRequest;
+
+    fn prompt(terminal: bool, input: &str) -> (Vec<String>, String) {
+        let types = Annotations::List(vec!["".to_owned(), "enum:start|stop".to_owned()]);
+        let descriptions = Annotations::List(vec!["unit".to_owned()]);
+        let mut parser =
+            ArgParser::new("systemctl {1} {0}", Some(&descriptions)).types(Some(&types));
+        parser.parse().unwrap();
+
+        let mut args = vec![];
+        let mut prompts = vec![];
+        let mut request = ExecutionRequest::new("service", vec![])
+            .terminal(terminal)
+            .input(Cursor::new(input.to_owned()))
+            .prompts(&mut prompts);
+        super::missing(true, &parser, &mut args, &mut request).unwrap();
+        drop(request);
+
+        (args, String::from_utf8(prompts).unwrap())
+    }
+
+    #[test]
+    fn missing() {
+        let (args, output) = prompt(true, "nginx\n\nstart\n");
+        assert_eq!(args, vec!["nginx", "start"]);
+        assert_eq!(
+            output,
+            "0 (unit): 1 (No description provided) [one of start|stop]: 1 (No description provided) [one of start|stop]: "
+        );
+
+        let (args, output) = prompt(true, "nginx\n");
+        assert_eq!(args, vec!["nginx"]);
+        assert!(output.starts_with("0 (unit): "));
+
+        // never prompts without terminal
+        let (args, output) = prompt(false, "nginx\nstart\n");
+        assert!(args.is_empty());
+        assert_eq!(output, "");
+    }
+}
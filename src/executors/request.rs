@@ -0,0 +1,131 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use termcolor::{ColorChoice, StandardStream, WriteColor};
+
+use crate::backend::{Backend, Inherit};
+use crate::interpreter::Registry;
+use crate::placeholders::Context;
+
+/**
+ * arguments for executing a function, independent of the command-line parser
+ */
+pub struct ExecutionRequest<'a> {
+    pub name: String,
+    pub args: Vec<String>, // extra arguments passed to the function
+    pub verbose: bool,
+    pub dry_run: bool,
+    pub prompt: bool, // prompt for missing arguments even if the function does not opt in
+    pub terminal: bool, // input is a terminal, prompting is never done without it
+    pub config_dir: Option<PathBuf>, // directory of the configuration file, for `{config_dir}`
+    pub interpreters: Registry, // interpreters of the configuration file
+    pub input: Box<dyn BufRead + 'a>, // source of answers for prompts
+    pub prompts: Box<dyn Write + 'a>, // sink for prompts, stderr for keeping stdout clean
+    pub output: Box<dyn WriteColor + 'a>, // sink for verbose and dry-run messages
+    pub backend: Box<dyn Backend + 'a>, // how to spawn the process
+}
+
+impl<'a> ExecutionRequest<'a> {
+    pub fn new(name: &str, args: Vec<String>) -> ExecutionRequest<'a> {
+        ExecutionRequest {
+            name: name.to_owned(),
+            args,
+            verbose: false,
+            dry_run: false,
+            prompt: false,
+            terminal: false,
+            config_dir: None,
+            interpreters: Registry::default(),
+            input: Box::new(BufReader::new(io::stdin())),
+            prompts: Box::new(io::stderr()),
+            output: Box::new(StandardStream::stdout(ColorChoice::Always)),
+            backend: Box::new(Inherit),
+        }
+    }
+
+    pub fn verbose(mut self, verbose: bool) -> ExecutionRequest<'a> {
+        self.verbose = verbose;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> ExecutionRequest<'a> {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn prompt(mut self, prompt: bool) -> ExecutionRequest<'a> {
+        self.prompt = prompt;
+        self
+    }
+
+    pub fn terminal(mut self, terminal: bool) -> ExecutionRequest<'a> {
+        self.terminal = terminal;
+        self
+    }
+
+    pub fn config_dir(mut self, config_dir: &Path) -> ExecutionRequest<'a> {
+        self.config_dir = Some(config_dir.to_owned());
+        self
+    }
+
+    pub fn interpreters(mut self, interpreters: &Registry) -> ExecutionRequest<'a> {
+        self.interpreters = interpreters.to_owned();
+        self
+    }
+
+    pub fn input(mut self, input: impl BufRead + 'a) -> ExecutionRequest<'a> {
+        self.input = Box::new(input);
+        self
+    }
+
+    pub fn prompts(mut self, prompts: impl Write + 'a) -> ExecutionRequest<'a> {
+        self.prompts = Box::new(prompts);
+        self
+    }
+
+    pub fn output(mut self, output: impl WriteColor + 'a) -> ExecutionRequest<'a> {
+        self.output = Box::new(output);
+        self
+    }
+
+    pub fn backend(mut self, backend: impl Backend + 'a) -> ExecutionRequest<'a> {
+        self.backend = Box::new(backend);
+        self
+    }
+
+    // values for built-in placeholders of this request
+    pub fn context(&self) -> Context {
+        Context::new(&self.name, self.config_dir.to_owned())
+    }
+}
+
+/**
+ * arguments for displaying details of a function
+ */
+pub struct DisplayRequest<'a> {
+    pub name: String,
+    pub subcommand: Option<String>,
+    pub interpreters: Registry, // for checking availability
+    pub output: Box<dyn WriteColor + 'a>,
+}
+
+impl<'a> DisplayRequest<'a> {
+    pub fn new(name: &str, subcommand: Option<&str>) -> DisplayRequest<'a> {
+        DisplayRequest {
+            name: name.to_owned(),
+            subcommand: subcommand.map(|s| s.to_owned()),
+            interpreters: Registry::default(),
+            output: Box::new(StandardStream::stdout(ColorChoice::Auto)),
+        }
+    }
+
+    pub fn interpreters(mut self, interpreters: &Registry) -> DisplayRequest<'a> {
+        self.interpreters = interpreters.to_owned();
+        self
+    }
+
+    pub fn output(mut self, output: impl WriteColor + 'a) -> DisplayRequest<'a> {
+        self.output = Box::new(output);
+        self
+    }
+}
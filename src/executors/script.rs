@@ -0,0 +1,399 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+use tempfile::{Builder, NamedTempFile};
+use termcolor::Color;
+
+use crate::backend::Invocation;
+use crate::cache;
+use crate::error::Error;
+use crate::executors::{self, validate, DisplayRequest, ExecutionRequest, Executor, Issue};
+use crate::fmt;
+use crate::interpreter::Registry;
+use crate::pad;
+use crate::shell::{self, Unavailable};
+
+/**
+ * script works as a script file, arguments are passed as `$1..$n` (or `sys.argv`)
+ */
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Script {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<String>, // inline script, written to a temporary file when executed
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    script: Option<String>, // path to a script file, relative to the configuration file
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interpreter: Option<String>, // e.g. `bash` or `python3`, defaults to `sh`
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    condition: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    condition_ttl: Option<String>, // e.g. `1h`, cache the result of condition
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_flags: Option<Vec<String>>, // defaults to `--help` and `-h`
+}
+
+impl Script {
+    pub fn new(body: &str, interpreter: Option<&str>, description: Option<&str>) -> Script {
+        Script {
+            body: Some(body.to_owned()),
+            script: None,
+            interpreter: interpreter.map(|s| s.to_owned()),
+            condition: None,
+            condition_ttl: None,
+            cwd: None,
+            description: description.map(|s| s.to_owned()),
+            help_flags: None,
+        }
+    }
+
+    pub fn from_file(script: &str, interpreter: Option<&str>, description: Option<&str>) -> Script {
+        Script {
+            body: None,
+            script: Some(script.to_owned()),
+            ..Script::new("", interpreter, description)
+        }
+    }
+
+    fn interpreter(&self) -> &str {
+        match &self.interpreter {
+            Some(interpreter) => interpreter,
+            None => "sh",
+        }
+    }
+
+    // program configured for the interpreter in `interpreters`, otherwise the name itself
+    fn program(&self, interpreters: &Registry) -> String {
+        let name = self.interpreter();
+        match interpreters.get(name) {
+            Ok(interpreter) => interpreter.program(name).to_owned(),
+            Err(_) => name.to_owned(),
+        }
+    }
+
+    // external script file, relative paths are resolved from the configuration directory
+    fn path(&self, config_dir: &Option<PathBuf>) -> Option<PathBuf> {
+        let script = PathBuf::from(self.script.as_ref()?);
+        match config_dir {
+            Some(dir) if script.is_relative() => Some(dir.join(script)),
+            _ => Some(script),
+        }
+    }
+}
+
+/**
+ * inline script written to the temporary directory, removed when dropped
+ */
+struct TempScript {
+    file: NamedTempFile,
+}
+
+impl TempScript {
+    // created exclusively with a random suffix and owner-only permissions
+    fn create(name: &str, interpreter: &str, body: &str) -> Result<TempScript, Error> {
+        let name: String = name
+            .chars()
+            .map(
+                |c| match c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    true => c,
+                    false => '_',
+                },
+            )
+            .collect();
+        let prefix = format!("cet-{}-", name);
+        let io_error = |e: std::io::Error| Error::Io {
+            path: std::env::temp_dir(),
+            message: format!("could not write script {} because {}", name, e),
+        };
+
+        let mut file = Builder::new()
+            .prefix(&prefix)
+            .tempfile()
+            .map_err(io_error)?;
+        write!(file, "#!/usr/bin/env {}\n{}", interpreter, body).map_err(io_error)?;
+        file.flush().map_err(io_error)?;
+
+        Ok(TempScript { file })
+    }
+
+    fn path(&self) -> &Path {
+        self.file.path()
+    }
+}
+
+#[typetag::serde(name = "script")]
+impl Executor for Script {
+    fn can_execute(&self, interpreters: &Registry) -> Result<(), Unavailable> {
+        shell::probe(interpreters, self.interpreter(), &None)?;
+        cache::probe(interpreters, "sh", &self.condition, &self.condition_ttl)
+    }
+
+    fn execute(&self, request: &mut ExecutionRequest) -> Result<ExitStatus, failure::Error> {
+        if executors::is_help(&self.help_flags, request.args.first()) {
+            let output = request.output.as_mut();
+            self.display(
+                &mut DisplayRequest::new(&request.name, None)
+                    .interpreters(&request.interpreters)
+                    .output(output),
+            )?;
+            return Ok(ExitStatus::default());
+        }
+
+        let (path, _temp) = match (self.path(&request.config_dir), &self.body) {
+            (Some(path), _) if !path.is_file() => {
+                let message = format!("script {} does not exist", path.display());
+                return Err(Error::Io { path, message }.into());
+            }
+            (Some(path), _) => (path, None),
+            (None, Some(body)) => {
+                let program = self.program(&request.interpreters);
+                let temp = TempScript::create(&request.name, &program, body)?;
+                (temp.path().to_owned(), Some(temp))
+            }
+            (None, None) => return Err(failure::err_msg("either `body` or `script` is required")),
+        };
+
+        // external scripts without `interpreter` are executed by their own shebang
+        let path = path.display().to_string();
+        let cwd = shell::working_dir(&self.cwd, &request.config_dir)?;
+        let mut invocation = match (&self.script, &self.interpreter) {
+            (Some(_), None) => Invocation::new(&path, &[]),
+            _ => Invocation::new(&self.program(&request.interpreters), &[&path]),
+        }
+        .current_dir(cwd.as_deref());
+        invocation.args.extend(request.args.iter().cloned());
+
+        let execute = std::iter::once(&invocation.program)
+            .chain(invocation.args.iter())
+            .map(|w| fmt::quote(w))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        if request.verbose {
+            let output = request.output.as_mut();
+            fmt::write_label(output, "Executing", Color::Green, &execute)?;
+        }
+
+        if request.dry_run {
+            let output = request.output.as_mut();
+            fmt::write_label(output, "dry-run", Color::Blue, &execute)?;
+            return Err(failure::err_msg("executed as a dry run"));
+        }
+
+        match request.backend.run(&invocation) {
+            Ok(status) => Ok(status),
+            Err(e) => Err(failure::err_msg(format!("function failed: {}", e))),
+        }
+    }
+
+    fn display(&self, request: &mut DisplayRequest) -> Result<(), failure::Error> {
+        let description = match &self.description {
+            Some(value) => fmt::to_single_line(value),
+            None => "No description provided".to_owned(),
+        };
+        let script = match (&self.script, &self.body) {
+            (Some(path), _) => format!("Script         : {}", path),
+            (None, Some(body)) => format!(
+                "Execute        : {}",
+                pad::left_pad_without_1st(body.trim_end(), 17)
+            ),
+            (None, None) => "Execute        : ".to_owned(),
+        };
+
+        writeln!(
+            request.output,
+            "\
+Usage (Cet)    : cet exec {name} -- <EXTRA ARGS>
+Usage (Direct) : {name} <EXTRA ARGS>
+{script}
+Interpreter    : {interpreter}
+Status         : {status}
+
+{description}",
+            name = request.name,
+            script = script,
+            interpreter = match (&self.script, &self.interpreter) {
+                (Some(_), None) => "(shebang of the script)",
+                _ => self.interpreter(),
+            },
+            status = fmt::to_status(&self.can_execute(&request.interpreters)),
+            description = description.trim(),
+        )?;
+
+        Ok(())
+    }
+
+    fn complete(&self, _args: &[String]) -> Vec<String> {
+        vec![]
+    }
+
+    fn export_as(&self, name: &str) -> Result<String, failure::Error> {
+        Ok(format!(
+            "alias {name}='cet exec {name} -- '",
+            name = name.to_owned()
+        ))
+    }
+
+    fn description(&self) -> &str {
+        match &self.description {
+            Some(value) => value,
+            None => "No description provided",
+        }
+    }
+
+    fn is_help(&self, args: &[String]) -> bool {
+        executors::is_help(&self.help_flags, args.first())
+    }
+
+    fn validate(&self, _interpreters: &Registry, config_dir: &Path) -> Vec<Issue> {
+        let mut issues = vec![];
+        issues.extend(validate::ttl(&self.condition_ttl));
+        issues.extend(validate::cwd(&self.cwd, config_dir));
+
+        match (&self.body, &self.script) {
+            (Some(_), Some(_)) => issues.push(Issue::new(
+                "script",
+                "`body` and `script` cannot be used together",
+            )),
+            (None, None) => {
+                issues.push(Issue::new("body", "either `body` or `script` is required"))
+            }
+            (None, Some(script)) if !config_dir.join(script).is_file() => {
+                let msg = format!("script `{}` does not exist", script);
+                issues.push(Issue::new("script", &msg));
+            }
+            _ => {}
+        }
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use termcolor::Buffer;
+
+    use super::{Script, TempScript};
+    use crate::backend::Capture;
+    use crate::executors::{ExecutionRequest, Executor};
+    use crate::interpreter::Registry;
+
+    #[test]
+    fn execute_with_arguments() {
+        let script = Script::new("echo \"$#:$1\"\nexit 3\n", None, None);
+        let args = vec!["my notes.txt".to_owned(), "b".to_owned()];
+
+        let mut backend = Capture::new();
+        let mut request = ExecutionRequest::new("notes", args).backend(&mut backend);
+        let status = script.execute(&mut request).unwrap();
+        drop(request);
+
+        assert_eq!(status.code(), Some(3));
+        assert_eq!(backend.last().unwrap().stdout, b"2:my notes.txt\n");
+    }
+
+    #[test]
+    fn execute_with_registered_interpreter() {
+        let registry: Registry = toml::from_str("[mysh]\nprogram = '/bin/sh'").unwrap();
+        let script = Script::new("echo \"$1\"", Some("mysh"), None);
+
+        let mut backend = Capture::new();
+        let mut request = ExecutionRequest::new("greet", vec!["hello".to_owned()])
+            .interpreters(&registry)
+            .backend(&mut backend);
+        let status = script.execute(&mut request).unwrap();
+        drop(request);
+
+        assert!(status.success());
+        assert_eq!(backend.last().unwrap().stdout, b"hello\n");
+
+        let script = Script::from_file("/bin/sh", Some("mysh"), None);
+        let mut buffer = Buffer::no_color();
+        let mut request = ExecutionRequest::new("run", vec![])
+            .interpreters(&registry)
+            .dry_run(true)
+            .output(&mut buffer);
+        script.execute(&mut request).unwrap_err();
+        drop(request);
+
+        assert_eq!(
+            String::from_utf8(buffer.into_inner()).unwrap(),
+            "dry-run: /bin/sh /bin/sh\n"
+        );
+    }
+
+    #[test]
+    fn execute_as_dry_run() {
+        let script = Script::from_file("/bin/sh", Some("bash"), None);
+        let args = vec!["a b".to_owned()];
+
+        let mut buffer = Buffer::no_color();
+        let mut request = ExecutionRequest::new("run", args)
+            .dry_run(true)
+            .output(&mut buffer);
+        let result = script.execute(&mut request);
+        drop(request);
+
+        assert_eq!(result.unwrap_err().to_string(), "executed as a dry run");
+        assert_eq!(
+            String::from_utf8(buffer.into_inner()).unwrap(),
+            "dry-run: bash /bin/sh 'a b'\n"
+        );
+    }
+
+    #[test]
+    fn temp_script_is_created_exclusively() {
+        let a = TempScript::create("../notes", "sh", "echo").unwrap();
+        let b = TempScript::create("../notes", "sh", "echo").unwrap();
+
+        assert_ne!(a.path(), b.path());
+        assert_eq!(a.path().parent(), Some(std::env::temp_dir().as_path()));
+        assert!(a.path().to_str().unwrap().contains("cet-___notes-"));
+        assert_eq!(
+            std::fs::read_to_string(a.path()).unwrap(),
+            "#!/usr/bin/env sh\necho"
+        );
+
+        let path = a.path().to_owned();
+        drop(a);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn validate() {
+        let script: Script = toml::from_str("body = 'echo'\nscript = 'run.sh'").unwrap();
+        assert_eq!(
+            script.validate(&Registry::default(), Path::new("/"))[0].message,
+            "`body` and `script` cannot be used together"
+        );
+
+        let script: Script = toml::from_str("interpreter = 'python3'").unwrap();
+        assert_eq!(
+            script.validate(&Registry::default(), Path::new("/"))[0].message,
+            "either `body` or `script` is required"
+        );
+
+        // relative scripts are resolved from the configuration directory
+        let script: Script = toml::from_str("script = 'bin/sh'").unwrap();
+        assert!(script
+            .validate(&Registry::default(), Path::new("/"))
+            .is_empty());
+        assert_eq!(
+            script.validate(&Registry::default(), Path::new("/nowhere"))[0].message,
+            "script `bin/sh` does not exist"
+        );
+    }
+}
@@ -1,15 +1,20 @@
 use std::collections::BTreeMap;
 use std::io::Write;
-use std::process::{Command, ExitStatus};
+use std::path::Path;
+use std::process::ExitStatus;
 
-use clap::ArgMatches;
-use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+use termcolor::{Color, WriteColor};
 
-use crate::argparse::ArgParser;
-use crate::executors::Executor;
+use crate::argparse::{Annotations, ArgParser};
+use crate::cache;
+use crate::error::Error;
+use crate::executors::{self, prompt, validate, DisplayRequest, ExecutionRequest, Executor, Issue};
 use crate::fmt;
+use crate::interpreter::Registry;
 use crate::pad;
+use crate::placeholders;
 use crate::shell;
+use crate::shell::Unavailable;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SubCommand {
@@ -18,9 +23,21 @@ pub struct SubCommand {
     #[serde(skip_serializing_if = "Option::is_none")]
     condition: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    condition_ttl: Option<String>, // e.g. `1h`, cache the result of condition
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>, // shared by all sub-commands
+
     #[serde(skip_serializing_if = "Option::is_none")]
     description: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_flags: Option<Vec<String>>, // defaults to `--help` and `-h`
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt: Option<bool>, // prompt for missing arguments of sub-functions on terminal
+
     #[serde(skip_serializing_if = "Option::is_none")]
     shell: Option<String>,
 
@@ -35,7 +52,10 @@ pub struct Function {
     description: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    descriptions: Option<Vec<String>>, // description for arguments
+    descriptions: Option<Annotations>, // description for arguments
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    types: Option<Annotations>, // type of arguments, e.g. `int` or `enum:start|stop`
 }
 
 impl SubCommand {
@@ -44,10 +64,12 @@ impl SubCommand {
         name: &str,
         command: &str,
         condition: Option<&str>,
+        cwd: Option<&str>,
         description: Option<&str>,
         shell: Option<&str>,
     ) -> SubCommand {
         let condition = condition.map(|s| s.to_owned());
+        let cwd = cwd.map(|s| s.to_owned());
         let description = description.map(|s| s.to_owned());
         let shell = shell.map(|s| s.to_owned());
         let mut subcommands = BTreeMap::new();
@@ -57,13 +79,18 @@ impl SubCommand {
                 command: command.to_owned(),
                 description,
                 descriptions: None,
+                types: None,
             },
         );
 
         SubCommand {
             command: program.to_owned(),
             condition,
+            condition_ttl: None,
+            cwd,
             description: None,
+            help_flags: None,
+            prompt: None,
             shell,
             subcommands,
         }
@@ -74,27 +101,17 @@ impl SubCommand {
     }
 
     pub fn exists(&self, name: &str) -> bool {
-        self.subcommands.get(name).is_some()
+        self.subcommands.contains_key(name)
     }
 
     pub fn add(&mut self, executor: SubCommand) -> Result<(), failure::Error> {
         for (key, value) in executor.subcommands.iter() {
             if self.exists(key) {
-                let msg = format!("sub-function `{}` is already exists in this function", key);
-                return Err(failure::err_msg(msg));
+                let name = key.to_owned();
+                return Err(Error::SubFunctionAlreadyExists { name }.into());
             }
 
-            let description = value.description.as_ref().map(|s| s.to_owned());
-            let descriptions = value.descriptions.as_ref().cloned();
-
-            self.subcommands.insert(
-                key.to_owned(),
-                Function {
-                    command: value.command.to_owned(),
-                    description,
-                    descriptions,
-                },
-            );
+            self.subcommands.insert(key.to_owned(), value.clone());
         }
 
         Ok(())
@@ -106,62 +123,59 @@ impl SubCommand {
             return Ok(());
         }
 
-        let msg = format!("sub-function `{}` is not exists in this function", name);
-        Err(failure::err_msg(msg))
+        let name = name.to_owned();
+        Err(Error::SubFunctionNotFound { name }.into())
     }
 
     pub fn has_subcommands(&self) -> bool {
         !self.subcommands.is_empty()
     }
 
-    fn format_args(&self, arg: &str) -> Result<String, ()> {
-        if arg.contains(' ') {
-            Ok(format!("\"{}\"", arg))
-        } else {
-            Ok(arg.to_owned())
-        }
-    }
-
     fn run_command(
         &self,
         execute: &str,
-        show_verbose: bool,
-        dry_run: bool,
+        request: &mut ExecutionRequest,
     ) -> Result<ExitStatus, failure::Error> {
-        if show_verbose {
-            let mut stdout = StandardStream::stdout(ColorChoice::Always);
-            let mut clrspc = ColorSpec::new();
-            clrspc.set_bold(true).set_fg(Some(Color::Green));
-            stdout.set_color(&clrspc)?;
-            write!(&mut stdout, "Executing")?;
-
-            clrspc.set_bold(false).set_fg(None);
-            stdout.set_color(&clrspc)?;
-            writeln!(&mut stdout, ": {}", execute.replace("\n", ""))?;
-            stdout.flush()?;
+        if request.verbose {
+            let output = request.output.as_mut();
+            fmt::write_label(output, "Executing", Color::Green, execute)?;
         }
 
-        if dry_run {
-            let mut stdout = StandardStream::stdout(ColorChoice::Always);
-            let mut clrspc = ColorSpec::new();
-            clrspc.set_bold(true).set_fg(Some(Color::Blue));
-            stdout.set_color(&clrspc)?;
-            write!(&mut stdout, "dry-run")?;
-
-            clrspc.set_bold(false).set_fg(None);
-            stdout.set_color(&clrspc)?;
-            writeln!(&mut stdout, ": {}", execute.replace("\n", ""))?;
-            stdout.flush()?;
-
+        if request.dry_run {
+            let output = request.output.as_mut();
+            fmt::write_label(output, "dry-run", Color::Blue, execute)?;
             return Err(failure::err_msg("executed as a dry run"));
         }
 
-        shell::safe_run(self.shell(), execute, None)
+        let cwd = shell::working_dir(&self.cwd, &request.config_dir)?;
+
+        shell::safe_run(
+            request.backend.as_mut(),
+            &request.interpreters,
+            self.shell(),
+            execute,
+            cwd.as_deref(),
+        )
+    }
+
+    fn help(
+        &self,
+        request: &mut ExecutionRequest,
+        subcommand: Option<&str>,
+    ) -> Result<ExitStatus, failure::Error> {
+        let output = request.output.as_mut();
+        self.display(
+            &mut DisplayRequest::new(&request.name, subcommand)
+                .interpreters(&request.interpreters)
+                .output(output),
+        )?;
+
+        Ok(ExitStatus::default())
     }
 
     fn shell(&self) -> &str {
         match &self.shell {
-            Some(shell) => &shell,
+            Some(shell) => shell,
             None => "sh",
         }
     }
@@ -169,81 +183,90 @@ impl SubCommand {
 
 #[typetag::serde(name = "subcommand")]
 impl Executor for SubCommand {
-    fn can_execute(&self) -> bool {
-        if self.shell() != "sh" {
-            match Command::new(self.shell()).arg("--version").output() {
-                Ok(_) => {}
-                Err(_) => return false,
-            };
-        }
-
-        if let Some(condition) = &self.condition {
-            #[rustfmt::skip]
-            return match Command::new(self.shell()).args(&["-c", &condition]).output() {
-                Ok(value) => value.status.success(),
-                Err(_) => false,
-            };
-        }
-
-        true
+    fn can_execute(&self, interpreters: &Registry) -> Result<(), Unavailable> {
+        cache::probe(
+            interpreters,
+            self.shell(),
+            &self.condition,
+            &self.condition_ttl,
+        )
     }
 
-    fn execute(&self, args: &ArgMatches) -> Result<ExitStatus, failure::Error> {
-        let extra: Vec<String> = args.values_of("extra").map_or_else(
-            || vec![],
-            |w| w.map(|v| self.format_args(v).unwrap()).collect(),
-        );
-        let show_verbose = args.is_present("verbose");
-        let dry_run = args.is_present("dry_run");
+    fn execute(&self, request: &mut ExecutionRequest) -> Result<ExitStatus, failure::Error> {
+        // names are looked up by raw arguments, extra arguments are quoted by the rule of the interpreter
+        let extra = request.args.to_owned();
+        let quoting = request.interpreters.get(self.shell())?.quoting();
+        let quote = |args: &[String]| {
+            args.iter()
+                .map(|w| quoting.quote(w))
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+
+        if executors::is_help(&self.help_flags, extra.first()) {
+            return self.help(request, None);
+        }
 
         // run original
         if extra.is_empty() {
-            return self.run_command(&self.command, show_verbose, dry_run);
+            return self.run_command(&self.command, request);
         }
 
         // subcommand does not assume anything other than the single command.
-        if !self.exists(extra.get(0).unwrap()) {
+        if !self.exists(extra.first().unwrap()) {
             let mut execute = self.command.to_owned();
-            execute.push_str(&format!(" {}", extra.join(" ")));
+            execute.push_str(&format!(" {}", quote(&extra)));
 
-            return self.run_command(&execute, show_verbose, dry_run);
+            return self.run_command(&execute, request);
         }
 
         // building
         let mut execute = self.command.to_owned();
-        let executor = self.get(extra.get(0).unwrap()).unwrap();
-        let mut parser = ArgParser::new(&executor.command, None);
+        let name = extra.first().unwrap();
+        let executor = self.get(name).unwrap();
+        let mut parser = ArgParser::new(&executor.command, executor.descriptions.as_ref())
+            .types(executor.types.as_ref())
+            .quoting(quoting)
+            .context(request.context());
         parser.parse()?;
 
+        // sub-functions without parameters pass `--help` to the wrapped command
+        if parser.has_arguments()? && executors::is_help(&self.help_flags, extra.get(1)) {
+            return self.help(request, Some(name));
+        }
+
         if parser.has_arguments()? {
-            let extra = match extra.get(1..) {
-                Some(value) => value.iter().map(|s| s.to_string()).collect::<Vec<String>>(),
-                None => vec![],
-            };
-            execute.push_str(&format!(" {}", parser.fill(extra)?));
+            // raw arguments, they are quoted by parser after type checking
+            let mut extra = request.args.get(1..).map_or_else(Vec::new, |w| w.to_vec());
+            prompt::missing(self.prompt.unwrap_or(false), &parser, &mut extra, request)?;
+            let filled = parser
+                .fill(extra)
+                .map_err(|e| e.with_usage(parser.usage(&format!("{} {}", request.name, name))))?;
+            execute.push_str(&format!(" {}", filled));
         } else {
-            execute.push_str(&format!(" {}", &executor.command));
+            let command = placeholders::resolve(&executor.command, &request.context(), quoting)?;
+            execute.push_str(&format!(" {}", placeholders::unescape(&command)));
 
             if let Some(extra) = extra.get(1..) {
-                let extra = extra.iter().map(|s| s.to_string()).collect::<Vec<String>>();
-                execute.push_str(&format!(" {}", extra.join(" ")));
+                execute.push_str(&format!(" {}", quote(extra)));
             }
         }
 
-        self.run_command(&execute, show_verbose, dry_run)
+        self.run_command(&execute, request)
     }
 
-    fn display(&self, args: &ArgMatches) -> Result<(), failure::Error> {
-        let name = args.value_of("name").unwrap();
-        let sub = args.value_of("subcommand");
-
-        if let Some(sub) = sub {
+    fn display(&self, request: &mut DisplayRequest) -> Result<(), failure::Error> {
+        let name = request.name.as_str();
+        if let Some(sub) = &request.subcommand {
             let subcommand = self.subcommands.get(sub);
+            let output = request.output.as_mut();
             return match subcommand {
-                Some(value) => value.display(&name, &sub),
+                Some(value) => {
+                    value.display(name, sub, &self.can_execute(&request.interpreters), output)
+                }
                 None => {
-                    let msg = format!("subcommand `{}` is not exists in this function", sub);
-                    Err(failure::err_msg(msg))
+                    let name = sub.to_owned();
+                    Err(Error::SubFunctionNotFound { name }.into())
                 }
             };
         }
@@ -266,12 +289,14 @@ impl Executor for SubCommand {
             })
             .collect::<Vec<String>>();
 
-        println!(
+        writeln!(
+            request.output,
             "\
 Usage (Cet)    : cet exec {name} -- <EXTRA ARGS>
 Usage (Direct) : {name} <EXTRA ARGS>
 Wrapped        : {command}
 Shell          : {shell}
+Status         : {status}
 
 {description}
 
@@ -281,15 +306,33 @@ SubCommands (show details of subcommand, pass `-s <name>`):
             description = description,
             command = pad::left_pad_without_1st(&self.command, 17),
             shell = self.shell(),
+            status = fmt::to_status(&self.can_execute(&request.interpreters)),
             subcommands = subcommands
                 .iter()
                 .map(|w| format!("    {}", w))
                 .collect::<Vec<String>>()
                 .join("\n")
-        );
+        )?;
         Ok(())
     }
 
+    fn complete(&self, args: &[String]) -> Vec<String> {
+        if args.len() <= 1 {
+            return self.subcommands.keys().cloned().collect();
+        }
+
+        let executor = match self.get(&args[0]) {
+            Some(value) => value,
+            None => return vec![],
+        };
+
+        let mut parser = ArgParser::new(&executor.command, None).types(executor.types.as_ref());
+        match parser.parse() {
+            Ok(_) => parser.completions(args.len() - 2),
+            Err(_) => vec![],
+        }
+    }
+
     fn export_as(&self, name: &str) -> Result<String, failure::Error> {
         Ok(format!(
             "alias {name}='cet exec {name} -- '",
@@ -303,49 +346,83 @@ SubCommands (show details of subcommand, pass `-s <name>`):
             None => "No description provided",
         }
     }
+
+    // same as `execute`, sub-functions without parameters pass `--help` to the wrapped command
+    fn is_help(&self, args: &[String]) -> bool {
+        if executors::is_help(&self.help_flags, args.first()) {
+            return true;
+        }
+
+        let executor = match args.first().and_then(|w| self.get(w)) {
+            Some(value) => value,
+            None => return false,
+        };
+        let mut parser = ArgParser::new(&executor.command, None);
+        parser.parse().is_ok()
+            && parser.has_arguments().unwrap_or(false)
+            && executors::is_help(&self.help_flags, args.get(1))
+    }
+
+    fn validate(&self, interpreters: &Registry, config_dir: &Path) -> Vec<Issue> {
+        let mut issues = vec![];
+        issues.extend(validate::shell(interpreters, self.shell()));
+        issues.extend(validate::ttl(&self.condition_ttl));
+        issues.extend(validate::cwd(&self.cwd, config_dir));
+
+        for (key, value) in self.subcommands.iter() {
+            let prefix = format!("subcommands.{}.", key);
+            issues.extend(validate::template(
+                &prefix,
+                &value.command,
+                &value.descriptions,
+            ));
+            issues.extend(validate::types(&prefix, &value.types));
+        }
+
+        issues
+    }
 }
 
 impl Function {
-    fn display(&self, parent: &str, myself: &str) -> Result<(), failure::Error> {
+    fn display(
+        &self,
+        parent: &str,
+        myself: &str,
+        status: &Result<(), Unavailable>,
+        output: &mut dyn WriteColor,
+    ) -> Result<(), failure::Error> {
         let description = match &self.description {
             Some(value) => value,
             None => "No description provided",
         };
-        let descriptions = match &self.descriptions {
-            Some(values) => Some(values.iter().map(|s| s.as_str()).collect()),
-            None => None,
-        };
-        let mut parser = ArgParser::new(&self.command, descriptions);
+        let mut parser =
+            ArgParser::new(&self.command, self.descriptions.as_ref()).types(self.types.as_ref());
         parser.parse()?;
 
         let parameters = match parser.arguments() {
             Some(values) => values
                 .iter()
-                .enumerate()
-                .map(|(i, w)| {
-                    format!(
-                        "{index} ({opt}): {description}",
-                        index = i,
-                        opt = w.attribute(),
-                        description = fmt::to_single_line(w.description())
-                    )
-                })
+                .map(fmt::to_parameter)
                 .collect::<Vec<String>>(),
             None => vec!["No description provided".to_owned()],
         };
 
-        println!(
+        writeln!(
+            output,
             "\
 Usage (Cet)    : cet exec {parent} -- {myself} <EXTRA ARGS>
 Usage (Direct) : {parent} {myself} <EXTRA ARGS>
-Wrapped        : {parent} {command}",
+Wrapped        : {parent} {command}
+Status         : {status}",
             parent = parent,
             myself = myself,
             command = self.command,
-        );
+            status = fmt::to_status(status),
+        )?;
 
         if !parameters.is_empty() {
-            println!(
+            writeln!(
+                output,
                 "\
 Parameters     :
 {parameters}",
@@ -354,11 +431,142 @@ Parameters     :
                     .map(|w| format!("    {}", w))
                     .collect::<Vec<String>>()
                     .join("\n"),
-            );
+            )?;
         }
 
-        println!("\n{description}", description = description.trim(),);
+        writeln!(output, "\n{}", description.trim())?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use termcolor::Buffer;
+
+    use super::SubCommand;
+    use crate::executors::{DisplayRequest, ExecutionRequest, Executor};
+
+    fn docker() -> SubCommand {
+        let mut executor = SubCommand::new("docker", "c", "container", None, None, None, None);
+        let prune = SubCommand::new("docker", "prune", "{0} prune", None, None, None, None);
+        executor.add(prune).unwrap();
+
+        executor
+    }
+
+    fn dry_run(executor: &SubCommand, args: &[&str]) -> String {
+        let args = args.iter().map(|s| s.to_string()).collect();
+        let mut buffer = Buffer::no_color();
+        let request = ExecutionRequest::new("docker", args).dry_run(true);
+        let result = executor.execute(&mut request.output(&mut buffer));
+
+        assert_eq!(result.unwrap_err().to_string(), "executed as a dry run");
+        String::from_utf8(buffer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn execute_as_dry_run() {
+        let executor = docker();
+
+        assert_eq!(dry_run(&executor, &[]), "dry-run: docker\n");
+        assert_eq!(
+            dry_run(&executor, &["c", "ls"]),
+            "dry-run: docker container ls\n"
+        );
+        assert_eq!(
+            dry_run(&executor, &["prune", "image"]),
+            "dry-run: docker image prune\n"
+        );
+        assert_eq!(dry_run(&executor, &["ps", "-a"]), "dry-run: docker ps -a\n");
+
+        // extra arguments are quoted
+        assert_eq!(
+            dry_run(&executor, &["run", "it's", "$HOME", "a b"]),
+            "dry-run: docker run 'it'\\''s' '$HOME' 'a b'\n"
+        );
+        assert_eq!(
+            dry_run(&executor, &["c", "ls", "--format", "{{.ID}} $x"]),
+            "dry-run: docker container ls --format '{{.ID}} $x'\n"
+        );
+    }
+
+    #[test]
+    fn execute_without_required_arguments() {
+        let executor = docker();
+        let args = vec!["prune".to_owned()];
+        let mut buffer = Buffer::no_color();
+        let request = ExecutionRequest::new("docker", args).dry_run(true);
+        let result = executor.execute(&mut request.output(&mut buffer));
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "missing argument {0}: 0 given, but 1 required\nusage: docker prune <arg0>"
+        );
+    }
+
+    #[test]
+    fn execute_help() {
+        let executor = docker();
+        let help = |args: &[&str]| {
+            let args = args.iter().map(|s| s.to_string()).collect();
+            let mut buffer = Buffer::no_color();
+            let request = ExecutionRequest::new("docker", args).dry_run(true);
+            let result = executor.execute(&mut request.output(&mut buffer));
+
+            (
+                result.is_ok(),
+                String::from_utf8(buffer.into_inner()).unwrap(),
+            )
+        };
+
+        let (ok, output) = help(&["--help"]);
+        assert!(ok);
+        assert!(output.contains("SubCommands (show details of subcommand, pass `-s <name>`):"));
+
+        let (ok, output) = help(&["prune", "-h"]);
+        assert!(ok);
+        assert!(output.starts_with("Usage (Cet)    : cet exec docker -- prune <EXTRA ARGS>\n"));
+
+        // sub-functions without parameters pass it to the wrapped command
+        let (ok, output) = help(&["c", "--help"]);
+        assert!(!ok);
+        assert_eq!(output, "dry-run: docker container --help\n");
+    }
+
+    #[test]
+    fn display_subcommand() {
+        let executor = docker();
+        let mut buffer = Buffer::no_color();
+        executor
+            .display(&mut DisplayRequest::new("docker", Some("prune")).output(&mut buffer))
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(buffer.into_inner()).unwrap(),
+            "\
+Usage (Cet)    : cet exec docker -- prune <EXTRA ARGS>
+Usage (Direct) : docker prune <EXTRA ARGS>
+Wrapped        : docker {0} prune
+Status         : available
+Parameters     :
+    0 (required): No description provided
+
+No description provided
+"
+        );
+    }
+
+    #[test]
+    fn display_unknown_subcommand() {
+        let executor = docker();
+        let mut buffer = Buffer::no_color();
+        let result =
+            executor.display(&mut DisplayRequest::new("docker", Some("x")).output(&mut buffer));
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "sub-function `x` is not exists in this function"
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::path::Path;
+
+use crate::argparse::{Annotations, ArgParser, ParamType};
+use crate::cache;
+use crate::executors::Issue;
+use crate::interpreter::Registry;
+use crate::options::{self, Options};
+use crate::placeholders;
+use crate::shell;
+
+pub fn shell(interpreters: &Registry, shell: &str) -> Option<Issue> {
+    match interpreters.get(shell) {
+        Ok(_) => None,
+        Err(e) => Some(Issue::new("shell", &e.to_string())),
+    }
+}
+
+pub fn ttl(ttl: &Option<String>) -> Option<Issue> {
+    match ttl.as_ref().map(|w| cache::parse_ttl(w)) {
+        Some(Err(e)) => Some(Issue::new("condition_ttl", &e.to_string())),
+        _ => None,
+    }
+}
+
+// relative paths are resolved from the directory of the configuration file, as when executed
+pub fn cwd(cwd: &Option<String>, config_dir: &Path) -> Option<Issue> {
+    let cwd = match cwd {
+        Some(value) => value,
+        None => return None,
+    };
+
+    // variables may be set only at runtime
+    if cwd.contains('$') {
+        return None;
+    }
+
+    match shell::expand(cwd).map(|w| config_dir.join(w)) {
+        Ok(path) if !path.is_dir() => {
+            let msg = format!("directory `{}` does not exist", cwd);
+            Some(Issue::new("cwd", &msg))
+        }
+        _ => None,
+    }
+}
+
+pub fn template(prefix: &str, command: &str, descriptions: &Option<Annotations>) -> Vec<Issue> {
+    let key = |name: &str| format!("{}{}", prefix, name);
+
+    let mut parser = ArgParser::new(command, None);
+    if let Err(e) = parser.parse() {
+        return vec![Issue::new(&key("command"), &e.to_string())];
+    }
+
+    let mut issues: Vec<Issue> = placeholders::quoted(command)
+        .iter()
+        .map(|placeholder| {
+            let msg = format!(
+                "{} is inside quotes and its value is substituted without quoting",
+                placeholder
+            );
+            Issue::warning(&key("command"), &msg)
+        })
+        .collect();
+
+    let arguments = parser
+        .arguments()
+        .map_or_else(Vec::new, |w| w.iter().collect());
+    match descriptions {
+        Some(Annotations::Table(values)) => issues.extend(
+            values
+                .keys()
+                .filter(|name| !arguments.iter().any(|w| w.matches(name)))
+                .map(|name| {
+                    let msg = format!("description `{}` does not match any parameter", name);
+                    Issue::new(&key("descriptions"), &msg)
+                }),
+        ),
+        Some(values) if values.len() > arguments.len() => {
+            let msg = format!(
+                "{} descriptions are given, but the command has only {} parameter(s)",
+                values.len(),
+                arguments.len()
+            );
+            issues.push(Issue::new(&key("descriptions"), &msg));
+        }
+        _ => {}
+    }
+
+    issues
+}
+
+pub fn types(prefix: &str, types: &Option<Annotations>) -> Vec<Issue> {
+    let values = match types {
+        Some(Annotations::List(values)) => values.iter().collect(),
+        Some(Annotations::Table(values)) => values.values().collect(),
+        None => vec![],
+    };
+
+    values
+        .into_iter()
+        .filter(|w| !w.trim().is_empty())
+        .filter_map(|w| ParamType::parse(w).err())
+        .map(|e| Issue::new(&format!("{}types", prefix), &e))
+        .collect()
+}
+
+pub fn options(command: &str, options: &Options) -> Vec<Issue> {
+    let mut issues: Vec<Issue> = options::placeholders(command)
+        .into_iter()
+        .filter(|name| !options.names().contains(&name.as_str()))
+        .map(|name| {
+            let msg = format!("option `{}` is not declared in `options`", name);
+            Issue::new("command", &msg)
+        })
+        .collect();
+
+    for name in options.names() {
+        if let Some(short) = options.short(name) {
+            if short.chars().count() != 1 {
+                let msg = format!("short option must be a single character: {}", short);
+                issues.push(Issue::new(&format!("options.{}.short", name), &msg));
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::argparse::Annotations;
+    use crate::interpreter::Registry;
+
+    #[test]
+    fn shell() {
+        assert!(super::shell(&Registry::default(), "bash").is_none());
+        assert_eq!(
+            super::shell(&Registry::default(), "ash").unwrap().message,
+            "not supported shell: ash"
+        );
+    }
+
+    #[test]
+    fn ttl() {
+        assert!(super::ttl(&None).is_none());
+        assert!(super::ttl(&Some("1h".to_owned())).is_none());
+        assert_eq!(
+            super::ttl(&Some("1 hour".to_owned())).unwrap().message,
+            "invalid ttl: 1 hour"
+        );
+    }
+
+    #[test]
+    fn cwd() {
+        let cwd = |value: &str| super::cwd(&Some(value.to_owned()), Path::new("/"));
+        assert!(super::cwd(&None, Path::new("/")).is_none());
+        assert!(cwd("/").is_none());
+        assert!(cwd("$HOME/path").is_none());
+        assert!(cwd("tmp").is_none());
+        assert_eq!(
+            cwd("relative/to/config").unwrap().message,
+            "directory `relative/to/config` does not exist"
+        );
+        assert_eq!(
+            cwd("/path/to/nowhere").unwrap().message,
+            "directory `/path/to/nowhere` does not exist"
+        );
+    }
+
+    #[test]
+    fn template() {
+        let descriptions = Some(Annotations::List(vec!["first".to_owned()]));
+        assert!(super::template("", "tail {0}", &descriptions).is_empty());
+        assert!(super::template("", "tail {0}", &None).is_empty());
+
+        let issues = super::template("subcommands.c.", "tail", &descriptions);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].key, "subcommands.c.descriptions");
+        assert_eq!(
+            issues[0].message,
+            "1 descriptions are given, but the command has only 0 parameter(s)"
+        );
+
+        let descriptions = Some(toml::from_str("'0' = 'file'\n'1..' = 'files'").unwrap());
+        assert!(super::template("", "cp {0} {1..}", &descriptions).is_empty());
+
+        let issues = super::template("", "cp {0} {1}", &descriptions);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].message,
+            "description `1..` does not match any parameter"
+        );
+    }
+
+    #[test]
+    fn options() {
+        let options = toml::from_str("verbose = { short = 'vv' }").unwrap();
+        let issues = super::options("tail {opt:verbose} {opt:count}", &options);
+
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].key, "command");
+        assert_eq!(
+            issues[0].message,
+            "option `count` is not declared in `options`"
+        );
+        assert_eq!(issues[1].key, "options.verbose.short");
+    }
+
+    #[test]
+    fn types() {
+        let types = Some(Annotations::List(vec![
+            "int".to_owned(),
+            "enum:start|stop".to_owned(),
+            "float".to_owned(),
+        ]));
+        let issues = super::types("subcommands.c.", &types);
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].key, "subcommands.c.types");
+        assert_eq!(issues[0].message, "unknown type: float");
+    }
+}
@@ -1,3 +1,59 @@
+use std::io;
+
+use termcolor::{Color, ColorSpec, WriteColor};
+
+use crate::argparse::Argument;
+use crate::shell::Unavailable;
+
 pub fn to_single_line(string: &str) -> String {
     string.replace("\n", " ").trim().to_owned()
 }
+
+// quotes the argument as a single word of POSIX shells, e.g. `it's` to `'it'\''s'`
+pub fn quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-.,/:=@%+".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+// e.g. `1.. (required, 1 or more, existing file): files`
+pub fn to_parameter(argument: &Argument) -> String {
+    let mut attributes = vec![argument.attribute().to_owned()];
+    attributes.extend(argument.count());
+    attributes.extend(argument.kind().map(|w| w.to_string()));
+    let attribute = attributes.join(", ");
+
+    format!(
+        "{} ({}): {}",
+        argument.label(),
+        attribute,
+        to_single_line(argument.description())
+    )
+}
+
+pub fn to_status(status: &Result<(), Unavailable>) -> String {
+    match status {
+        Ok(_) => "available".to_owned(),
+        Err(e) => format!("unavailable — {}", e),
+    }
+}
+
+// writes `label: message` with colored label, e.g. `Executing: ls -al`
+pub fn write_label(
+    output: &mut dyn WriteColor,
+    label: &str,
+    color: Color,
+    message: &str,
+) -> io::Result<()> {
+    let mut clrspc = ColorSpec::new();
+    clrspc.set_bold(true).set_fg(Some(color));
+    output.set_color(&clrspc)?;
+    write!(output, "{}", label)?;
+
+    output.reset()?;
+    writeln!(output, ": {}", message.replace("\n", ""))?;
+    output.flush()
+}
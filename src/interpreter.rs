@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::fmt;
+
+/**
+ * interpreters defined in `[interpreters]` of a configuration, they override built-ins
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(transparent)]
+pub struct Registry {
+    entries: BTreeMap<String, Interpreter>,
+}
+
+/**
+ * how inline commands are passed to an interpreter, e.g. `pwsh -Command`
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Interpreter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    program: Option<String>, // binary, defaults to the name of the interpreter
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flag: Option<String>, // argv flag for inline code, defaults to `-c`
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prefix: Option<String>, // prepended to the command, e.g. `command` for skipping shell functions
+
+    #[serde(default, skip_serializing_if = "is_posix")]
+    quoting: Quoting,
+}
+
+fn is_posix(quoting: &Quoting) -> bool {
+    *quoting == Quoting::Posix
+}
+
+/**
+ * quoting rule for arguments substituted into the command
+ */
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Quoting {
+    #[default]
+    Posix, // `'it'\''s'`
+    Powershell,    // `'it''s'`
+    EscapedSingle, // `'it\'s'`, e.g. Python, Ruby and Node.js
+    EscapedDouble, // `"say \"hi\""`, e.g. Nushell
+}
+
+impl Quoting {
+    pub fn quote(self, arg: &str) -> String {
+        let escape = |quote: char| {
+            arg.replace('\\', "\\\\")
+                .replace(quote, &format!("\\{}", quote))
+        };
+        match self {
+            Quoting::Posix => fmt::quote(arg),
+            Quoting::Powershell => format!("'{}'", arg.replace('\'', "''")),
+            Quoting::EscapedSingle => format!("'{}'", escape('\'')),
+            Quoting::EscapedDouble => format!("\"{}\"", escape('"')),
+        }
+    }
+}
+
+impl Interpreter {
+    fn new(flag: &str, prefix: Option<&str>, quoting: Quoting) -> Interpreter {
+        Interpreter {
+            program: None,
+            flag: Some(flag.to_owned()),
+            prefix: prefix.map(|s| s.to_owned()),
+            quoting,
+        }
+    }
+
+    pub fn program<'a>(&'a self, name: &'a str) -> &'a str {
+        self.program.as_deref().unwrap_or(name)
+    }
+
+    pub fn flag(&self) -> &str {
+        self.flag.as_deref().unwrap_or("-c")
+    }
+
+    pub fn quoting(&self) -> Quoting {
+        self.quoting
+    }
+
+    // shells understand shell commands such as conditions, unlike Python, Ruby, Node.js or Perl
+    pub fn is_shell(&self) -> bool {
+        self.quoting != Quoting::EscapedSingle
+    }
+
+    // e.g. `command ls -al`
+    pub fn prefixed(&self, command: &str) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{} {}", prefix, command),
+            None => command.to_owned(),
+        }
+    }
+
+    // searches the program in `PATH`, because not all interpreters support `--version`
+    pub fn locate(&self, name: &str) -> io::Result<PathBuf> {
+        let program = Path::new(self.program(name));
+        if program.components().count() > 1 {
+            return match program.is_file() {
+                true => Ok(program.to_owned()),
+                false => Err(io::Error::new(io::ErrorKind::NotFound, "no such file")),
+            };
+        }
+
+        env::var_os("PATH")
+            .iter()
+            .flat_map(env::split_paths)
+            .map(|w| w.join(program))
+            .find(|w| w.is_file())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "not found in PATH"))
+    }
+}
+
+fn builtin(name: &str) -> Option<Interpreter> {
+    let interpreter = match name {
+        "sh" | "bash" | "zsh" | "dash" | "ksh" | "fish" => {
+            Interpreter::new("-c", Some("command"), Quoting::Posix)
+        }
+        "nu" => Interpreter::new("-c", None, Quoting::EscapedDouble),
+        "pwsh" | "powershell" => Interpreter::new("-Command", None, Quoting::Powershell),
+        "python" | "python3" => Interpreter::new("-c", None, Quoting::EscapedSingle),
+        "ruby" | "node" | "perl" => Interpreter::new("-e", None, Quoting::EscapedSingle),
+        _ => return None,
+    };
+
+    Some(interpreter)
+}
+
+impl Registry {
+    pub fn new(entries: BTreeMap<String, Interpreter>) -> Registry {
+        Registry { entries }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Result<Interpreter, Error> {
+        match self.entries.get(name).cloned().or_else(|| builtin(name)) {
+            Some(interpreter) => Ok(interpreter),
+            None => Err(Error::UnsupportedShell {
+                shell: name.to_owned(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{Interpreter, Quoting, Registry};
+
+    #[test]
+    fn quote() {
+        assert_eq!(Quoting::Posix.quote("it's"), "'it'\\''s'");
+        assert_eq!(Quoting::Powershell.quote("it's"), "'it''s'");
+        assert_eq!(Quoting::EscapedSingle.quote("it's \\n"), "'it\\'s \\\\n'");
+        assert_eq!(
+            Quoting::EscapedDouble.quote("say \"hi\""),
+            "\"say \\\"hi\\\"\""
+        );
+    }
+
+    #[test]
+    fn registry() {
+        let registry = Registry::default();
+        assert_eq!(registry.get("fish").unwrap().prefixed("ls"), "command ls");
+        assert_eq!(registry.get("pwsh").unwrap().flag(), "-Command");
+        assert_eq!(
+            registry.get("python3").unwrap().prefixed("print(1)"),
+            "print(1)"
+        );
+        assert_eq!(
+            registry.get("ash").unwrap_err().to_string(),
+            "not supported shell: ash"
+        );
+
+        let interpreter: Interpreter =
+            toml::from_str("program = '/usr/bin/env'\nflag = '-S'\nquoting = 'escaped_single'")
+                .unwrap();
+        let mut interpreters = BTreeMap::new();
+        interpreters.insert("centoria-test-env".to_owned(), interpreter.clone());
+        let registry = Registry::new(interpreters);
+
+        assert_eq!(registry.get("centoria-test-env").unwrap(), interpreter);
+        assert_eq!(interpreter.program("centoria-test-env"), "/usr/bin/env");
+        assert!(interpreter.locate("centoria-test-env").is_ok());
+        assert!(registry.get("sh").unwrap().locate("sh").is_ok());
+
+        // registries of other configurations are independent
+        assert!(Registry::default().get("centoria-test-env").is_err());
+    }
+}
@@ -0,0 +1,45 @@
+//! Centoria - function manager for macOS and Linux.
+//!
+//! This crate provides the configuration loader and executors used by `cet`,
+//! so that other tools can load a centoria configuration and run its functions.
+//!
+//! ```no_run
+//! use centoria::{Config, ExecutionRequest};
+//!
+//! let config = Config::load()?;
+//! let mut request = ExecutionRequest::new("search", vec!["Hello".to_owned()]);
+//! let status = config.execute(&mut request)?;
+//! # Ok::<(), failure::Error>(())
+//! ```
+
+#[macro_use]
+extern crate downcast_rs;
+#[macro_use]
+extern crate serde_derive;
+
+mod argparse;
+mod backend;
+mod cache;
+mod config;
+pub mod error;
+mod executors;
+mod fmt;
+mod interpreter;
+mod options;
+mod pad;
+mod placeholders;
+mod shell;
+#[cfg(unix)]
+mod signal;
+
+pub use argparse::{Annotations, ArgParser};
+pub use backend::{Backend, Capture, Inherit, Invocation, Recorder};
+pub use cache::clear as clear_cache;
+pub use config::Config;
+pub use error::Error;
+pub use executors::{
+    Alias, DisplayRequest, ExecutionRequest, Executor, Function, Issue, Script, SubCommand,
+};
+pub use interpreter::{Quoting, Registry};
+pub use placeholders::PLACEHOLDERS;
+pub use shell::Unavailable;
@@ -1,33 +1,42 @@
 #[macro_use]
 extern crate clap;
-#[macro_use]
-extern crate downcast_rs;
-#[macro_use]
-extern crate serde_derive;
 
 mod app;
-mod argparse;
 mod commands;
-mod config;
-mod executors;
-mod fmt;
-mod pad;
-mod shell;
 
+use std::env;
 use std::process::exit;
 
-use exitfailure::ExitFailure;
+use clap::ArgMatches;
 
-fn main() -> Result<(), ExitFailure> {
-    Ok(run()?)
-}
+use centoria::error;
 
-fn run() -> Result<(), failure::Error> {
+fn main() {
     let matches = app::build_app().get_matches();
 
+    if let Err(e) = run(&matches) {
+        if matches.value_of("error_format") == Some("json") {
+            eprintln!("{}", error::to_json(&e));
+        } else {
+            eprintln!("Error: {}", e);
+            for cause in e.iter_causes() {
+                eprintln!("Info: caused by {}", cause);
+            }
+        }
+
+        exit(error::exit_code(&e));
+    }
+}
+
+fn run(matches: &ArgMatches) -> Result<(), failure::Error> {
+    // `--config` is passed via environment, so that nested `cet` in functions also respects it
+    if let Some(path) = matches.value_of("config") {
+        env::set_var("CENTORIA_CONFIG_PATH", path);
+    }
+
     match matches.subcommand() {
-        ("init", Some(_)) => {
-            commands::init()?;
+        ("init", Some(matches)) => {
+            commands::init(matches)?;
         }
         ("add", Some(matches)) => {
             commands::add(matches)?;
@@ -37,9 +46,7 @@ fn run() -> Result<(), failure::Error> {
         }
         ("exec", Some(matches)) => {
             let status = commands::exec(matches)?;
-            if let Some(code) = status.code() {
-                exit(code);
-            }
+            exit(error::status_code(&status));
         }
         ("list", Some(_)) => {
             commands::list()?;
@@ -47,6 +54,18 @@ fn run() -> Result<(), failure::Error> {
         ("show", Some(matches)) => {
             commands::show(matches)?;
         }
+        ("complete", Some(matches)) => {
+            commands::complete(matches)?;
+        }
+        ("check", Some(_)) => {
+            commands::check()?;
+        }
+        ("doctor", Some(_)) => {
+            commands::doctor()?;
+        }
+        ("cache", Some(matches)) => {
+            commands::cache(matches)?;
+        }
         _ => {
             let msg = "subcommand is required";
             return Err(failure::err_msg(msg));
@@ -0,0 +1,332 @@
+use std::collections::BTreeMap;
+
+use regex::{Captures, Regex};
+
+use crate::error::Error;
+use crate::fmt;
+use crate::interpreter::Quoting;
+use crate::pad;
+use crate::placeholders;
+
+/**
+ * named options of a function, e.g. `verbose = { short = "v", flag = "--debug" }`
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(transparent)]
+pub struct Options {
+    entries: BTreeMap<String, OptionSpec>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct OptionSpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short: Option<String>, // e.g. `v` for `-v`, long name is always the key
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flag: Option<String>, // substituted when the option is given, defaults to `--<key>`
+
+    #[serde(default, skip_serializing_if = "is_false")]
+    value: bool, // takes a value, e.g. `-n 5`
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<String>, // substituted when the option is not given
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+// values of given options, keyed by option name
+pub type Values = BTreeMap<String, String>;
+
+impl Options {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.entries.keys().map(|s| s.as_str()).collect()
+    }
+
+    pub fn short(&self, name: &str) -> Option<&str> {
+        self.entries.get(name).and_then(|w| w.short.as_deref())
+    }
+
+    // splits arguments into option values and positional arguments, `--` ends options,
+    // short options can be combined as `-vf` and take attached values as `-n5`
+    pub fn parse(&self, args: &[String]) -> Result<(Values, Vec<String>), Error> {
+        // functions without options pass all arguments through, e.g. `rg -i`
+        if self.is_empty() {
+            return Ok((Values::new(), args.to_vec()));
+        }
+
+        let mut values = Values::new();
+        let mut positionals = vec![];
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                positionals.extend(args.by_ref().cloned());
+                break;
+            }
+
+            if !is_option(arg) {
+                positionals.push(arg.to_owned());
+                continue;
+            }
+
+            // `--count=5`
+            if arg.starts_with("--") || self.find(arg).is_some() {
+                let (option, inline) = match arg.find('=') {
+                    Some(index) if arg.starts_with("--") => {
+                        (&arg[..index], Some(&arg[index + 1..]))
+                    }
+                    _ => (arg.as_str(), None),
+                };
+                let (name, value) = self.value(option, inline, &mut args)?;
+                values.insert(name.to_owned(), value);
+                continue;
+            }
+
+            // `-vf` and `-n5`, the rest of a short option taking a value is the value
+            for (index, letter) in arg[1..].char_indices() {
+                let option = format!("-{}", letter);
+                let rest = &arg[1 + index + letter.len_utf8()..];
+                let (_, spec) = self.find_or_err(&option)?;
+                let inline = match rest {
+                    _ if !spec.value => None,
+                    "" => None,
+                    _ => Some(rest),
+                };
+
+                let (name, value) = self.value(&option, inline, &mut args)?;
+                values.insert(name.to_owned(), value);
+                if spec.value {
+                    break;
+                }
+            }
+        }
+
+        Ok((values, positionals))
+    }
+
+    // replaces `{opt:name}` with the given flag or quoted value, default value or empty string,
+    // values inside of quotes of the command are not quoted again
+    pub fn substitute(&self, command: &str, values: &Values, quoting: Quoting) -> String {
+        let placeholder = Regex::new(r"\{opt:(?P<name>[\w-]+)\}").unwrap();
+
+        placeholders::replace_unescaped(command, &placeholder, |captures: &Captures, quoted| {
+            let name = &captures["name"];
+            match (values.get(name), self.entries.get(name)) {
+                (Some(value), Some(spec)) if spec.value && !quoted => {
+                    placeholders::escape(&quoting.quote(value))
+                }
+                (Some(value), _) => placeholders::escape(value),
+                (None, Some(spec)) => placeholders::escape(spec.default.as_deref().unwrap_or("")),
+                (None, None) => captures[0].to_owned(),
+            }
+        })
+    }
+
+    // e.g. `-v, --verbose : print debug logs`
+    pub fn describe(&self) -> Vec<String> {
+        let usages: Vec<(String, &OptionSpec)> = self
+            .entries
+            .iter()
+            .map(|(name, spec)| {
+                let mut usage = match &spec.short {
+                    Some(short) => format!("-{}, --{}", short, name),
+                    None => format!("    --{}", name),
+                };
+                if spec.value {
+                    usage.push_str(&format!(" <{}>", name.to_uppercase()));
+                }
+
+                (usage, spec)
+            })
+            .collect();
+
+        let longest = usages.iter().map(|(w, _)| w.len()).max().unwrap_or(0);
+        usages
+            .iter()
+            .map(|(usage, spec)| {
+                let description = match &spec.description {
+                    Some(value) => fmt::to_single_line(value),
+                    None => "No description provided".to_owned(),
+                };
+
+                match &spec.default {
+                    Some(default) => format!(
+                        "{} : {} (default: {})",
+                        pad::right_pad(usage, longest),
+                        description,
+                        default
+                    ),
+                    None => format!("{} : {}", pad::right_pad(usage, longest), description),
+                }
+            })
+            .collect()
+    }
+
+    fn find_or_err(&self, option: &str) -> Result<(&str, &OptionSpec), Error> {
+        self.find(option).ok_or_else(|| Error::UnknownOption {
+            option: option.to_owned(),
+            known: self.flags(),
+        })
+    }
+
+    // value of the option, given inline, taken from the next argument or the flag itself
+    fn value<'a, I>(
+        &self,
+        option: &str,
+        inline: Option<&str>,
+        args: &mut I,
+    ) -> Result<(&str, String), Error>
+    where
+        I: Iterator<Item = &'a String>,
+    {
+        let (name, spec) = self.find_or_err(option)?;
+        if !spec.value {
+            return match &spec.flag {
+                Some(flag) => Ok((name, flag.to_owned())),
+                None => Ok((name, format!("--{}", name))),
+            };
+        }
+
+        match inline.or_else(|| args.next().map(|s| s.as_str())) {
+            Some(value) => Ok((name, value.to_owned())),
+            None => Err(Error::OptionValueMissing {
+                option: option.to_owned(),
+            }),
+        }
+    }
+
+    fn find(&self, option: &str) -> Option<(&str, &OptionSpec)> {
+        self.entries
+            .iter()
+            .find(|(name, spec)| match option.strip_prefix("--") {
+                Some(long) => long == name.as_str(),
+                None => spec.short.as_deref() == option.strip_prefix('-'),
+            })
+            .map(|(name, spec)| (name.as_str(), spec))
+    }
+
+    // e.g. `-v` and `--verbose`
+    pub fn flags(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .flat_map(|(name, spec)| {
+                let short = spec.short.as_ref().map(|w| format!("-{}", w));
+                short.into_iter().chain(Some(format!("--{}", name)))
+            })
+            .collect()
+    }
+}
+
+// `-v` and `--verbose` are options, but `-`, `-1` and `-1.5` are not
+fn is_option(arg: &str) -> bool {
+    arg.len() > 1 && arg.starts_with('-') && arg[1..].parse::<f64>().is_err()
+}
+
+// names of options referred by `{opt:name}` in the command
+pub fn placeholders(command: &str) -> Vec<String> {
+    let placeholder = Regex::new(r"\{opt:(?P<name>[\w-]+)\}").unwrap();
+
+    placeholder
+        .captures_iter(&placeholders::strip_escaped(command))
+        .map(|w| w["name"].to_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Options;
+    use crate::interpreter::Quoting;
+
+    fn options() -> Options {
+        toml::from_str(
+            "\
+verbose = { short = 'v', flag = '--debug', description = 'print debug logs' }
+count = { short = 'n', value = true, default = '10' }
+",
+        )
+        .unwrap()
+    }
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse() {
+        let options = options();
+
+        let (values, positionals) = options.parse(&args(&["-v", "a", "-n", "5", "b"])).unwrap();
+        assert_eq!(values["verbose"], "--debug");
+        assert_eq!(values["count"], "5");
+        assert_eq!(positionals, args(&["a", "b"]));
+
+        let (values, positionals) = options
+            .parse(&args(&["--count=3", "--", "-v", "-1"]))
+            .unwrap();
+        assert_eq!(values["count"], "3");
+        assert_eq!(positionals, args(&["-v", "-1"]));
+
+        let (values, positionals) = options.parse(&args(&["-vn5", "a"])).unwrap();
+        assert_eq!(values["verbose"], "--debug");
+        assert_eq!(values["count"], "5");
+        assert_eq!(positionals, args(&["a"]));
+
+        let (values, _) = options.parse(&args(&["-vn", "3"])).unwrap();
+        assert_eq!(values["count"], "3");
+        assert_eq!(
+            options.parse(&args(&["-vx"])).unwrap_err().to_string(),
+            "unknown option `-x`, available options are: -n, --count, -v, --verbose"
+        );
+
+        assert_eq!(
+            options.parse(&args(&["-x"])).unwrap_err().to_string(),
+            "unknown option `-x`, available options are: -n, --count, -v, --verbose"
+        );
+        assert_eq!(
+            options.parse(&args(&["--count"])).unwrap_err().to_string(),
+            "option `--count` requires a value"
+        );
+    }
+
+    #[test]
+    fn substitute() {
+        let options = options();
+        let command = "tail {opt:verbose} -n {opt:count} {opt:unknown}";
+
+        let (values, _) = options.parse(&args(&["--verbose"])).unwrap();
+        assert_eq!(
+            options.substitute(command, &values, Quoting::Posix),
+            "tail --debug -n 10 {opt:unknown}"
+        );
+
+        let (values, _) = options.parse(&args(&["-n", "5; rm -rf ~"])).unwrap();
+        assert_eq!(
+            options.substitute(command, &values, Quoting::Posix),
+            "tail  -n '5; rm -rf ~' {opt:unknown}"
+        );
+        assert_eq!(
+            options.substitute("tail -n \"{opt:count}\"", &values, Quoting::Posix),
+            "tail -n \"5; rm -rf ~\""
+        );
+    }
+
+    #[test]
+    fn describe() {
+        assert_eq!(
+            options().describe(),
+            vec![
+                "-n, --count <COUNT> : No description provided (default: 10)",
+                "-v, --verbose       : print debug logs",
+            ]
+        );
+    }
+}
@@ -1,7 +1,7 @@
 #[allow(dead_code)]
 pub fn left_pad(string: &str, pad: usize) -> String {
     let spaces = checked_calculate(pad, string.len());
-    return format!("{}{}", " ".repeat(spaces), string);
+    format!("{}{}", " ".repeat(spaces), string)
 }
 
 pub fn left_pad_without_1st(string: &str, pad: usize) -> String {
@@ -20,14 +20,11 @@ pub fn left_pad_without_1st(string: &str, pad: usize) -> String {
 
 pub fn right_pad(string: &str, pad: usize) -> String {
     let spaces = checked_calculate(pad, string.len());
-    return format!("{}{}", string, " ".repeat(spaces));
+    format!("{}{}", string, " ".repeat(spaces))
 }
 
 fn checked_calculate(s1: usize, s2: usize) -> usize {
-    match s1.checked_sub(s2) {
-        Some(value) => value,
-        None => 0,
-    }
+    s1.saturating_sub(s2)
 }
 
 #[cfg(test)]
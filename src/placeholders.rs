@@ -0,0 +1,296 @@
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+use regex::{Captures, Regex};
+
+use crate::error::Error;
+use crate::interpreter::Quoting;
+
+// all placeholders available in templates, shown by `cet show --placeholders`
+pub const PLACEHOLDERS: [(&str, &str); 18] = [
+    ("{0}", "1st argument (required)"),
+    ("{0?}", "1st argument (optional)"),
+    ("{0..}", "1st and later arguments (1 or more)"),
+    ("{0..?}", "1st and later arguments (0 or more)"),
+    ("{0..2}", "1st and 2nd arguments"),
+    ("{-1}", "last argument (required)"),
+    ("{..-1}", "all arguments but the last (1 or more)"),
+    ("{-2..}", "last 2 arguments"),
+    (
+        "{0..|join:,}",
+        "1st and later arguments joined into a word by `,`",
+    ),
+    (
+        "{0..|each:-I %}",
+        "1st and later arguments, each formatted as `-I %`",
+    ),
+    ("{opt:NAME}", "value of option NAME declared in `options`"),
+    ("{env:NAME}", "environment variable NAME, empty if not set"),
+    (
+        "{env:NAME:-DEFAULT}",
+        "environment variable NAME, DEFAULT if not set or empty",
+    ),
+    ("{cwd}", "current working directory"),
+    ("{config_dir}", "directory of the configuration file"),
+    ("{name}", "name of the function"),
+    ("{git_root}", "root directory of the git repository of cwd"),
+    (
+        "{{0}}",
+        "literal `{0}`, any placeholder is escaped by doubling braces",
+    ),
+];
+
+/**
+ * values for built-in placeholders, e.g. `{name}` and `{config_dir}`
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Context {
+    pub name: String,
+    pub config_dir: Option<PathBuf>,
+}
+
+impl Context {
+    pub fn new(name: &str, config_dir: Option<PathBuf>) -> Context {
+        Context {
+            name: name.to_owned(),
+            config_dir,
+        }
+    }
+}
+
+fn pattern() -> Regex {
+    Regex::new(r"\{(?:env:(?P<env>\w+)(?::-(?P<default>[^}]*))?|(?P<builtin>cwd|config_dir|name|git_root))\}")
+        .unwrap()
+}
+
+// inside of any placeholder, e.g. `0..?`, `opt:lines` and `env:EDITOR:-vi`
+const INNER: &str = r"(?:-?\d+(?:\?|\.\.(?:-?\d+|\?)?)?|\.\.-\d+)(?:\|(?:join|each):[^{}]*)?|opt:[\w-]+|env:\w+(?::-[^{}]*)?|cwd|config_dir|name|git_root";
+
+// e.g. `{{0}}` and `{{env:HOME}}`, but not `{{.Name}}` of Go templates
+fn escaped() -> Regex {
+    Regex::new(&[r"\{\{(?P<literal>", INNER, r")\}\}"].concat()).unwrap()
+}
+
+// whether the command contains built-in placeholders
+pub fn contains(command: &str) -> bool {
+    pattern().is_match(command)
+}
+
+// replaces matches of `pattern` outside of escaped placeholders,
+// the replacer is told whether the match is inside of quotes of the command
+pub fn replace_unescaped<F>(command: &str, pattern: &Regex, mut replacer: F) -> String
+where
+    F: FnMut(&Captures, bool) -> String,
+{
+    let escaped = escaped();
+    let escapes = escaped.find_iter(command).map(|w| w.range());
+    let end = std::iter::once(command.len()..command.len());
+
+    let mut replaced = String::new();
+    let mut last = 0;
+    for escape in escapes.chain(end) {
+        let segment = &command[last..escape.start];
+        replaced.push_str(&pattern.replace_all(segment, |captures: &Captures| {
+            let quoted = is_quoted(command, last + captures.get(0).unwrap().start());
+            replacer(captures, quoted)
+        }));
+        replaced.push_str(&command[escape.clone()]);
+        last = escape.end;
+    }
+
+    replaced
+}
+
+// whether the position is inside of single or double quotes, e.g. `{0}` of `echo "{0}"`
+pub fn is_quoted(command: &str, position: usize) -> bool {
+    let mut quote = None;
+    let mut chars = command[..position].chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => {
+                chars.next();
+            }
+            (Some('"'), '"') => quote = None,
+            (None, '\'') | (None, '"') => quote = Some(c),
+            _ => {}
+        }
+    }
+
+    quote.is_some()
+}
+
+// placeholders inside of quotes, which are replaced without quoting, e.g. `{0}` of `echo "{0}"`
+pub fn quoted(command: &str) -> Vec<String> {
+    let placeholder = Regex::new(&[r"\{(?:", INNER, r")\}"].concat()).unwrap();
+
+    let mut found = vec![];
+    replace_unescaped(command, &placeholder, |captures: &Captures, quoted| {
+        if quoted {
+            found.push(captures[0].to_owned());
+        }
+        captures[0].to_owned()
+    });
+
+    found
+}
+
+// removes escaped placeholders, so that only placeholders to be replaced are left
+pub fn strip_escaped(command: &str) -> String {
+    escaped().replace_all(command, "").into_owned()
+}
+
+// escapes placeholders in a substituted value, so that it is never replaced again
+pub fn escape(value: &str) -> String {
+    let placeholder = Regex::new(&[r"\{(?P<literal>", INNER, r")\}"].concat()).unwrap();
+
+    placeholder.replace_all(value, "{{$literal}}").into_owned()
+}
+
+// `{{0}}` to `{0}`, done after all placeholders are replaced
+pub fn unescape(command: &str) -> String {
+    escaped().replace_all(command, "{$literal}").into_owned()
+}
+
+// replaces built-in placeholders with quoted values, empty values are left empty,
+// environment variables and placeholders inside of quotes are replaced as they are
+pub fn resolve(command: &str, context: &Context, quoting: Quoting) -> Result<String, Error> {
+    let mut error = None;
+    let resolved = replace_unescaped(
+        command,
+        &pattern(),
+        |captures: &Captures, quoted| match value(captures, context) {
+            Ok(value) if value.is_empty() => value,
+            Ok(value) if quoted || captures.name("env").is_some() => escape(&value),
+            Ok(value) => escape(&quoting.quote(&value)),
+            Err(e) => {
+                error.get_or_insert(e);
+                "".to_owned()
+            }
+        },
+    );
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(resolved),
+    }
+}
+
+fn value(captures: &Captures, context: &Context) -> Result<String, Error> {
+    let placeholder = captures[0].to_owned();
+    let failed = |message: &str| Error::Placeholder {
+        placeholder: placeholder.to_owned(),
+        message: message.to_owned(),
+    };
+
+    if let Some(name) = captures.name("env") {
+        let value = env::var(name.as_str()).unwrap_or_default();
+        return match captures.name("default") {
+            Some(default) if value.is_empty() => Ok(default.as_str().to_owned()),
+            _ => Ok(value),
+        };
+    }
+
+    match &captures["builtin"] {
+        "cwd" => match env::current_dir() {
+            Ok(path) => Ok(path.display().to_string()),
+            Err(e) => Err(failed(&e.to_string())),
+        },
+        "config_dir" => match &context.config_dir {
+            Some(path) => Ok(path.display().to_string()),
+            None => Err(failed("configuration file is not known")),
+        },
+        "name" => Ok(context.name.to_owned()),
+        "git_root" => {
+            let output = Command::new("git")
+                .args(["rev-parse", "--show-toplevel"])
+                .output();
+
+            match output {
+                Ok(output) if output.status.success() => {
+                    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+                }
+                Ok(_) => Err(failed("not in a git repository")),
+                Err(e) => Err(failed(&format!("could not run git: {}", e))),
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::path::PathBuf;
+
+    use super::{is_quoted, quoted, resolve, Context};
+    use crate::interpreter::Quoting;
+
+    #[test]
+    fn resolve_builtins() {
+        let context = Context::new("edit", Some(PathBuf::from("/etc/centoria")));
+
+        assert_eq!(
+            resolve("{name} {config_dir}/{0}", &context, Quoting::Posix).unwrap(),
+            "edit /etc/centoria/{0}"
+        );
+        assert_eq!(
+            resolve(
+                "{env:CENTORIA_TEST_UNSET:-vi} {env:CENTORIA_TEST_UNSET}!",
+                &context,
+                Quoting::Posix
+            )
+            .unwrap(),
+            "vi !"
+        );
+        assert_eq!(
+            resolve("{cwd}", &context, Quoting::Posix).unwrap(),
+            env::current_dir().unwrap().display().to_string()
+        );
+        assert_eq!(
+            resolve("{config_dir}", &Context::default(), Quoting::Posix)
+                .unwrap_err()
+                .to_string(),
+            "could not resolve {config_dir}: configuration file is not known"
+        );
+
+        // environment variables may hold commands with arguments, e.g. `code -w`
+        env::set_var("CENTORIA_TEST_EDITOR", "code -w");
+        assert_eq!(
+            resolve(
+                "{env:CENTORIA_TEST_EDITOR} {name}",
+                &context,
+                Quoting::Posix
+            )
+            .unwrap(),
+            "code -w edit"
+        );
+        assert_eq!(
+            resolve(
+                "{env:CENTORIA_TEST_UNSET:-code -w}",
+                &context,
+                Quoting::Posix
+            )
+            .unwrap(),
+            "code -w"
+        );
+
+        assert_eq!(
+            resolve("cd \"{config_dir}\"", &context, Quoting::Posix).unwrap(),
+            "cd \"/etc/centoria\""
+        );
+    }
+
+    #[test]
+    fn quotes() {
+        let command = r#"echo "{0}" '{1} "{2}"' \'{3} "\"{4}" {{5}}"#;
+        let positions: Vec<bool> = (0..5)
+            .map(|w| is_quoted(command, command.find(&format!("{{{}}}", w)).unwrap()))
+            .collect();
+
+        assert_eq!(positions, vec![true, true, true, false, true]);
+        assert_eq!(quoted(command), vec!["{0}", "{1}", "{2}", "{4}"]);
+    }
+}
@@ -1,14 +1,30 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus};
 
-// run command without existing aliases
+use regex::{Captures, Regex};
+
+use crate::backend::{Backend, Invocation};
+use crate::error;
+use crate::interpreter::Registry;
+
+// run command without existing aliases, in `cwd` if given
 pub fn safe_run(
+    backend: &mut dyn Backend,
+    interpreters: &Registry,
     shell: &str,
     command: &str,
-    cwd: Option<String>,
+    cwd: Option<&Path>,
 ) -> Result<ExitStatus, failure::Error> {
-    let command = create_command(shell, command, cwd);
+    let interpreter = interpreters.get(shell)?;
+    let command = create_command(interpreters, shell, command)?;
+    let invocation = Invocation::new(interpreter.program(shell), &[interpreter.flag(), &command])
+        .current_dir(cwd);
 
-    match Command::new(shell).args(&["-c", &command]).status() {
+    match backend.run(&invocation) {
         Ok(status) => Ok(status),
         Err(e) => {
             let msg = failure::err_msg(format!("function failed: {}", e));
@@ -17,61 +33,259 @@ pub fn safe_run(
     }
 }
 
-fn create_command(shell: &str, command: &str, cwd: Option<String>) -> String {
-    match cwd {
-        Some(value) => format!(
-            "cd {} {} command {}",
-            value,
-            chain_operator(shell).unwrap(),
-            command
-        ),
-        None => format!("command {}", command),
+/**
+ * reason why a function could not be executed
+ */
+#[derive(Serialize, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Unavailable {
+    // shell binary could not be spawned
+    MissingShell {
+        shell: String,
+        reason: String,
+    },
+
+    // condition exited with non-zero status (or was killed by signal)
+    ConditionFailed {
+        condition: String,
+        code: Option<i32>,
+        stderr: String,
+    },
+}
+
+impl Unavailable {
+    pub fn stderr(&self) -> Option<&str> {
+        match self {
+            Unavailable::ConditionFailed { stderr, .. } if !stderr.is_empty() => Some(stderr),
+            _ => None,
+        }
     }
 }
 
-fn chain_operator(shell: &str) -> Result<String, failure::Error> {
-    match shell {
-        "sh" => Ok("&&".to_owned()),
-        "bash" => Ok("&&".to_owned()),
-        "zsh" => Ok("&&".to_owned()),
-        "fish" => Ok("; and".to_owned()),
-        _ => {
-            let msg = failure::err_msg(format!("not supported shell: {}", shell));
-            Err(msg)
+impl fmt::Display for Unavailable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Unavailable::MissingShell { shell, reason } => {
+                write!(f, "shell `{}` is not available: {}", shell, reason)
+            }
+            Unavailable::ConditionFailed {
+                condition,
+                code: Some(code),
+                ..
+            } => write!(f, "condition `{}` exited {}", condition, code),
+            Unavailable::ConditionFailed { condition, .. } => {
+                write!(f, "condition `{}` was terminated by signal", condition)
+            }
         }
     }
 }
 
+impl Error for Unavailable {}
+
+// check that the shell is available and the condition (if any) succeeds,
+// conditions are evaluated by the shell of the function, or by `sh` for other interpreters
+pub fn probe(
+    interpreters: &Registry,
+    shell: &str,
+    condition: &Option<String>,
+) -> Result<(), Unavailable> {
+    let missing = |e: io::Error| Unavailable::MissingShell {
+        shell: shell.to_owned(),
+        reason: e.to_string(),
+    };
+
+    // interpreters not in the registry, e.g. ones of scripts, are only searched in `PATH`
+    let interpreter = interpreters.get(shell).ok();
+    interpreter
+        .clone()
+        .unwrap_or_default()
+        .locate(shell)
+        .map_err(missing)?;
+
+    let condition = match condition {
+        Some(value) => value,
+        None => return Ok(()),
+    };
+
+    let (name, program, flag) = match &interpreter {
+        Some(interpreter) if interpreter.is_shell() => {
+            (shell, interpreter.program(shell), interpreter.flag())
+        }
+        _ => ("sh", "sh", "-c"),
+    };
+    let output = Command::new(program)
+        .args([flag, condition])
+        .output()
+        .map_err(|e| Unavailable::MissingShell {
+            shell: name.to_owned(),
+            reason: e.to_string(),
+        })?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    Err(Unavailable::ConditionFailed {
+        condition: condition.to_owned(),
+        code: output.status.code(),
+        stderr: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+    })
+}
+
+fn create_command(
+    interpreters: &Registry,
+    shell: &str,
+    command: &str,
+) -> Result<String, failure::Error> {
+    Ok(interpreters.get(shell)?.prefixed(command))
+}
+
+// expands `~` and `$VAR` (or `${VAR}`) in a path, unset variables are errors
+pub fn expand(value: &str) -> Result<PathBuf, String> {
+    let value = match (value.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{}{}", home.display(), rest)
+        }
+        _ => value.to_owned(),
+    };
+
+    let mut unset = None;
+    let variable = Regex::new(r"\$(?:\{(?P<braced>\w+)\}|(?P<name>\w+))").unwrap();
+    let expanded = variable.replace_all(&value, |captures: &Captures| {
+        let name = captures.name("braced").or_else(|| captures.name("name"));
+        let name = name.unwrap().as_str();
+        env::var(name).unwrap_or_else(|_| {
+            unset.get_or_insert(format!("environment variable ${} is not set", name));
+            "".to_owned()
+        })
+    });
+
+    match unset {
+        Some(message) => Err(message),
+        None => Ok(PathBuf::from(expanded.as_ref())),
+    }
+}
+
+// resolves `cwd` of a function, relative paths are resolved from the configuration directory
+pub fn working_dir(
+    cwd: &Option<String>,
+    config_dir: &Option<PathBuf>,
+) -> Result<Option<PathBuf>, error::Error> {
+    let cwd = match cwd {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    let path = expand(cwd).map_err(|e| error::Error::Io {
+        path: PathBuf::from(cwd),
+        message: format!("could not expand working directory `{}`: {}", cwd, e),
+    })?;
+    let path = match config_dir {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path,
+    };
+
+    if !path.is_dir() {
+        let message = format!("working directory {} does not exist", path.display());
+        return Err(error::Error::Io { path, message });
+    }
+
+    Ok(Some(path))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{chain_operator, create_command};
+    use std::env;
+    use std::path::PathBuf;
+
+    use super::{create_command, expand, probe, working_dir};
+    use crate::interpreter::Registry;
 
     #[test]
-    fn chain_operator_test() {
-        assert_eq!(chain_operator("sh").unwrap(), "&&");
-        assert_eq!(chain_operator("bash").unwrap(), "&&");
-        assert_eq!(chain_operator("zsh").unwrap(), "&&");
-        assert_eq!(chain_operator("fish").unwrap(), "; and");
+    fn create_command_test() {
+        let registry = Registry::default();
+        let create = |shell: &str, command: &str| create_command(&registry, shell, command);
+        assert_eq!(create("bash", "ls -al").unwrap(), "command ls -al");
+        assert_eq!(create("fish", "ls -al").unwrap(), "command ls -al");
+        assert_eq!(create("python3", "print(1)").unwrap(), "print(1)");
         assert_eq!(
-            chain_operator("ash").unwrap_or_else(|err| err.to_string()),
+            create("ash", "ls").unwrap_err().to_string(),
             "not supported shell: ash"
         );
     }
 
     #[test]
-    fn create_command_test_for_cwd_is_none() {
-        assert_eq!(create_command("bash", "ls -al", None), "command ls -al");
+    fn expand_test() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand("~").unwrap(), home);
+        assert_eq!(expand("~/src").unwrap(), home.join("src"));
+        assert_eq!(expand("~user/src").unwrap(), PathBuf::from("~user/src"));
+
+        env::set_var("CENTORIA_TEST_CWD", "/tmp/my dir");
+        assert_eq!(
+            expand("$CENTORIA_TEST_CWD/a/${CENTORIA_TEST_CWD}").unwrap(),
+            PathBuf::from("/tmp/my dir/a//tmp/my dir")
+        );
+        assert_eq!(
+            expand("$CENTORIA_TEST_UNSET/a").unwrap_err(),
+            "environment variable $CENTORIA_TEST_UNSET is not set"
+        );
     }
 
     #[test]
-    fn create_command_test_for_cwd_is_not_none() {
+    fn working_dir_test() {
+        let root = Some(PathBuf::from("/"));
+        assert_eq!(working_dir(&None, &root).unwrap(), None);
         assert_eq!(
-            create_command("sh", "ls -al", Some("/path/to/cwd".to_owned())),
-            "cd /path/to/cwd && command ls -al"
+            working_dir(&Some("tmp".to_owned()), &root).unwrap(),
+            Some(PathBuf::from("/tmp"))
         );
         assert_eq!(
-            create_command("fish", "ls -al", Some("/path/to/cwd".to_owned())),
-            "cd /path/to/cwd ; and command ls -al"
+            working_dir(&Some("/path/to/nowhere".to_owned()), &root)
+                .unwrap_err()
+                .to_string(),
+            "working directory /path/to/nowhere does not exist"
+        );
+    }
+
+    #[test]
+    fn probe_test() {
+        let registry = Registry::default();
+        let probe = |shell: &str, condition: Option<&str>| {
+            probe(&registry, shell, &condition.map(|w| w.to_owned()))
+        };
+        assert!(probe("sh", None).is_ok());
+        assert!(probe("sh", Some("true")).is_ok());
+        assert_eq!(
+            probe("sh", Some("exit 3")).unwrap_err().to_string(),
+            "condition `exit 3` exited 3"
         );
+
+        let err = probe("sh", Some("echo oops >&2; false")).unwrap_err();
+        assert_eq!(err.to_string(), "condition `echo oops >&2; false` exited 1");
+        assert_eq!(err.stderr(), Some("oops"));
+
+        // conditions are evaluated by the shell of the function, and by `sh` for other interpreters
+        let registry: Registry = toml::from_str(
+            "\
+[centoria-test-errexit]
+program = 'sh'
+flag = '-ec'
+
+[centoria-test-env]
+program = '/usr/bin/env'
+quoting = 'escaped_single'
+",
+        )
+        .unwrap();
+        let condition = Some("false; true".to_owned());
+        assert!(super::probe(&Registry::default(), "sh", &condition).is_ok());
+        assert!(super::probe(&registry, "centoria-test-errexit", &condition).is_err());
+        assert!(super::probe(&registry, "centoria-test-env", &condition).is_ok());
+
+        let err = probe("nonexistent-shell", None).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("shell `nonexistent-shell` is not available"));
     }
 }
@@ -0,0 +1,145 @@
+use std::fs::File;
+use std::io;
+use std::mem;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Command, ExitStatus};
+use std::ptr;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use libc::{c_int, sigaction};
+
+// signals forwarded to the running function
+const SIGNALS: [c_int; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+static CHILD: AtomicI32 = AtomicI32::new(0);
+static PENDING: AtomicI32 = AtomicI32::new(0); // received before the child is spawned
+
+/**
+ * runs the child in its own process group and forwards signals to the whole group,
+ * the group takes the terminal while running if `cet` is in the foreground
+ */
+pub struct Forward {
+    previous: Vec<(c_int, sigaction)>,
+    terminal: Option<File>, // controlling terminal owned by `cet`
+}
+
+impl Forward {
+    // handlers are installed before spawning, so that no signal is lost
+    pub fn new() -> Forward {
+        PENDING.store(0, Ordering::SeqCst);
+
+        let previous = SIGNALS
+            .iter()
+            .filter_map(|signal| unsafe {
+                let mut action: sigaction = mem::zeroed();
+                action.sa_sigaction = forward as *const () as usize;
+                action.sa_flags = libc::SA_RESTART;
+                libc::sigemptyset(&mut action.sa_mask);
+
+                let mut previous: sigaction = mem::zeroed();
+                match libc::sigaction(*signal, &action, &mut previous) {
+                    0 => Some((*signal, previous)),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        let terminal = File::open("/dev/tty")
+            .ok()
+            .filter(|w| unsafe { libc::tcgetpgrp(w.as_raw_fd()) == libc::getpgrp() });
+
+        Forward { previous, terminal }
+    }
+
+    // puts the child into a new process group, which takes the terminal before exec
+    pub fn isolate(&self, command: &mut Command) {
+        let terminal = self.terminal.as_ref().map(|w| w.as_raw_fd());
+        unsafe {
+            command.pre_exec(move || {
+                if libc::setpgid(0, 0) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                if let Some(fd) = terminal {
+                    take_terminal(fd, libc::getpid());
+                }
+                Ok(())
+            });
+        }
+    }
+
+    // waits the child, and suspends `cet` together when the child is stopped, e.g. by Ctrl-Z
+    pub fn wait(&self, child: u32) -> io::Result<ExitStatus> {
+        let pid = child as i32;
+        CHILD.store(pid, Ordering::SeqCst);
+        match PENDING.swap(0, Ordering::SeqCst) {
+            0 => {}
+            signal => unsafe {
+                libc::kill(-pid, signal);
+            },
+        }
+
+        let options = match self.terminal {
+            Some(_) => libc::WUNTRACED,
+            None => 0,
+        };
+
+        loop {
+            let mut status = 0;
+            if unsafe { libc::waitpid(pid, &mut status, options) } < 0 {
+                let e = io::Error::last_os_error();
+                match e.kind() {
+                    io::ErrorKind::Interrupted => continue,
+                    _ => return Err(e),
+                }
+            }
+
+            if !libc::WIFSTOPPED(status) {
+                return Ok(ExitStatus::from_raw(status));
+            }
+
+            if let Some(terminal) = &self.terminal {
+                unsafe {
+                    take_terminal(terminal.as_raw_fd(), libc::getpgrp());
+                    libc::raise(libc::SIGSTOP);
+                    take_terminal(terminal.as_raw_fd(), pid);
+                    libc::kill(-pid, libc::SIGCONT);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Forward {
+    fn drop(&mut self) {
+        if let Some(terminal) = &self.terminal {
+            unsafe {
+                take_terminal(terminal.as_raw_fd(), libc::getpgrp());
+            }
+        }
+
+        for (signal, previous) in &self.previous {
+            unsafe {
+                libc::sigaction(*signal, previous, ptr::null_mut());
+            }
+        }
+
+        CHILD.store(0, Ordering::SeqCst);
+    }
+}
+
+// background process groups are stopped by SIGTTOU when taking the terminal without ignoring it
+unsafe fn take_terminal(fd: c_int, group: libc::pid_t) {
+    let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+    libc::tcsetpgrp(fd, group);
+    libc::signal(libc::SIGTTOU, previous);
+}
+
+extern "C" fn forward(signal: c_int) {
+    match CHILD.load(Ordering::SeqCst) {
+        0 => PENDING.store(signal, Ordering::SeqCst),
+        child => unsafe {
+            libc::kill(-child, signal);
+        },
+    }
+}
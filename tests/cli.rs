@@ -0,0 +1,728 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+use tempfile::TempDir;
+
+/**
+ * isolated environment for running `cet` against a temporary configuration
+ */
+struct Sandbox {
+    dir: TempDir,
+}
+
+impl Sandbox {
+    fn new() -> Sandbox {
+        let sandbox = Sandbox {
+            dir: TempDir::new().unwrap(),
+        };
+        fs::write(sandbox.config(), "").unwrap();
+
+        sandbox
+    }
+
+    fn with_config(toml: &str) -> Sandbox {
+        let sandbox = Sandbox::new();
+        fs::write(sandbox.config(), toml).unwrap();
+
+        sandbox
+    }
+
+    fn config(&self) -> PathBuf {
+        self.dir.path().join("centoria.toml")
+    }
+
+    fn saved(&self) -> String {
+        fs::read_to_string(self.config()).unwrap()
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_cet"));
+        command
+            .args(args)
+            .env("CENTORIA_CONFIG_PATH", self.config())
+            .env("HOME", self.dir.path())
+            .env("XDG_CONFIG_HOME", self.dir.path().join(".config"))
+            .env("XDG_CACHE_HOME", self.dir.path().join(".cache"))
+            .env("RUST_BACKTRACE", "0")
+            .env_remove("CENTORIA_INIT");
+
+        command
+    }
+
+    fn cet(&self, args: &[&str]) -> Output {
+        self.command(args).output().unwrap()
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn add_alias() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.cet(&["add", "search", "rg", "--condition", "which rg"]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        sandbox.saved(),
+        "\
+[search]
+runas = 'alias'
+command = 'rg'
+condition = 'which rg'
+shell = 'sh'
+"
+    );
+}
+
+#[test]
+fn add_function() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.cet(&["add", "logs", "tail -f /var/log/{0}/error.log"]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        sandbox.saved(),
+        "\
+[logs]
+runas = 'function'
+command = 'tail -f /var/log/{0}/error.log'
+shell = 'sh'
+"
+    );
+}
+
+#[test]
+fn add_function_with_escaped_placeholders() {
+    let sandbox = Sandbox::new();
+    sandbox.cet(&["add", "backup", "ls {0..} | xargs -I{{0}} echo {{0}}.bak"]);
+    assert!(sandbox.saved().contains("runas = 'function'\n"));
+
+    let output = sandbox.cet(&["exec", "backup", "--", "-d", "/"]);
+    assert_eq!(stdout(&output), "/.bak\n");
+}
+
+#[test]
+fn add_existing_function() {
+    let sandbox = Sandbox::new();
+    sandbox.cet(&["add", "search", "rg"]);
+    let output = sandbox.cet(&["add", "search", "ag"]);
+
+    assert_eq!(output.status.code(), Some(73));
+    assert!(stderr(&output).contains("function `search` is already exists"));
+    assert!(sandbox.saved().contains("command = 'rg'"));
+}
+
+#[test]
+fn add_subcommand() {
+    let sandbox = Sandbox::new();
+    sandbox.cet(&["add", "c", "container", "--program", "docker"]);
+    sandbox.cet(&["add", "prune", "{0} prune", "--program", "docker"]);
+
+    assert_eq!(
+        sandbox.saved(),
+        "\
+[docker]
+runas = 'subcommand'
+command = 'docker'
+shell = 'sh'
+[docker.subcommands.c]
+command = 'container'
+
+[docker.subcommands.prune]
+command = '{0} prune'
+"
+    );
+
+    let output = sandbox.cet(&["add", "c", "compose", "--program", "docker"]);
+    assert_eq!(output.status.code(), Some(73));
+    assert!(stderr(&output).contains("sub-function `c` is already exists in this function"));
+}
+
+#[test]
+fn remove() {
+    let sandbox = Sandbox::new();
+    sandbox.cet(&["add", "search", "rg"]);
+    sandbox.cet(&["add", "c", "container", "--program", "docker"]);
+
+    assert!(sandbox.cet(&["remove", "search"]).status.success());
+    assert!(sandbox
+        .cet(&["remove", "c", "--program", "docker"])
+        .status
+        .success());
+    assert_eq!(sandbox.saved(), "");
+
+    let output = sandbox.cet(&["remove", "search"]);
+    assert_eq!(output.status.code(), Some(127));
+    assert!(stderr(&output).contains("function `search` is not exists"));
+}
+
+#[test]
+fn exec() {
+    let sandbox = Sandbox::with_config(
+        "\
+[greet]
+runas = 'function'
+command = 'echo Hello, {0}'
+
+[quit]
+runas = 'alias'
+command = 'exit'
+",
+    );
+
+    let output = sandbox.cet(&["exec", "greet", "--", "world"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "Hello, world\n");
+
+    let output = sandbox.cet(&["exec", "quit", "--", "3"]);
+    assert_eq!(output.status.code(), Some(3));
+
+    let output = sandbox.cet(&["exec", "unknown"]);
+    assert_eq!(output.status.code(), Some(127));
+    assert!(stderr(&output).contains("function `unknown` is not exists"));
+}
+
+#[test]
+fn exec_unavailable_function() {
+    let sandbox = Sandbox::with_config(
+        "\
+[search]
+runas = 'alias'
+command = 'rg'
+condition = 'exit 1'
+",
+    );
+
+    let output = sandbox.cet(&["exec", "search"]);
+    assert_eq!(output.status.code(), Some(126));
+    assert!(stderr(&output)
+        .contains("could not execute the function `search`: condition `exit 1` exited 1"));
+}
+
+#[test]
+fn exec_help_of_unavailable_function() {
+    let sandbox = Sandbox::with_config(
+        "\
+[search]
+runas = 'function'
+command = 'rg {0}'
+condition = 'exit 1'
+",
+    );
+
+    let output = sandbox.cet(&["exec", "search", "--", "--help"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(
+        stdout(&output).contains("Status         : unavailable — condition `exit 1` exited 1\n")
+    );
+}
+
+#[test]
+fn broken_config() {
+    let sandbox = Sandbox::with_config("[search]\nrunas = 'alias'\ncommand = \n");
+    let output = sandbox.cet(&["list", "--error-format", "json"]);
+
+    assert_eq!(output.status.code(), Some(78));
+    assert!(
+        stderr(&output).starts_with(r#"{"code":78,"column":11,"kind":"config_parse","line":3,"#)
+    );
+}
+
+#[test]
+fn error_format_json() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.cet(&["--error-format", "json", "exec", "unknown"]);
+
+    assert_eq!(output.status.code(), Some(127));
+    assert_eq!(
+        stderr(&output),
+        "{\"code\":127,\"kind\":\"not_found\",\"message\":\"function `unknown` is not exists\",\"name\":\"unknown\"}\n"
+    );
+}
+
+#[test]
+fn exec_without_required_arguments() {
+    let sandbox = Sandbox::new();
+    sandbox.cet(&["add", "logs", "tail /var/log/{0}/error.log"]);
+
+    let output = sandbox.cet(&["exec", "logs"]);
+    assert_eq!(output.status.code(), Some(64));
+    assert_eq!(
+        stderr(&output),
+        "Error: missing argument {0}: 0 given, but 1 required\nusage: logs <arg0>\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_killed_by_signal() {
+    let sandbox = Sandbox::new();
+    sandbox.cet(&["add", "term", "kill -TERM $$"]);
+
+    let output = sandbox.cet(&["exec", "term"]);
+    assert_eq!(output.status.code(), Some(143));
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_forwards_signal() {
+    let sandbox = Sandbox::new();
+    sandbox.cet(&["add", "wait", "echo ready; sleep 10 | cat"]);
+
+    let mut child = sandbox
+        .command(&["exec", "wait"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    assert_eq!(line, "ready\n");
+
+    let started = Instant::now();
+    Command::new("kill")
+        .args(["-TERM", &child.id().to_string()])
+        .status()
+        .unwrap();
+
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(143));
+
+    // the whole process group is terminated, no grandchild keeps stdout open
+    stdout.read_to_string(&mut line).unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn exec_with_typed_arguments() {
+    let sandbox = Sandbox::with_config(
+        "\
+[service]
+runas = 'function'
+command = 'echo {0} {1}'
+types = ['enum:start|stop|restart', 'int']
+",
+    );
+
+    let output = sandbox.cet(&["exec", "service", "--", "start", "3"]);
+    assert_eq!(stdout(&output), "start 3\n");
+
+    let output = sandbox.cet(&["exec", "service", "--", "begin", "3"]);
+    assert_eq!(output.status.code(), Some(64));
+    assert_eq!(
+        stderr(&output),
+        "Error: invalid argument `begin` for {0}: expected one of start|stop|restart\n"
+    );
+
+    let output = sandbox.cet(&["complete", "service", "--", "st"]);
+    assert_eq!(stdout(&output), "start\nstop\n");
+
+    let output = sandbox.cet(&["show", "service"]);
+    assert!(stdout(&output).contains("    1 (required, int): No description provided\n"));
+}
+
+#[test]
+fn exec_with_builtin_placeholders() {
+    let sandbox = Sandbox::with_config(
+        "\
+[edit]
+runas = 'function'
+command = 'echo {name} {env:CENTORIA_EDITOR:-vi} {config_dir} {0}'
+",
+    );
+
+    let output = sandbox.cet(&["exec", "edit", "--", "notes"]);
+    let config_dir = sandbox.dir.path().display();
+    assert_eq!(stdout(&output), format!("edit vi {} notes\n", config_dir));
+
+    let output = sandbox
+        .command(&["exec", "edit", "--", "notes"])
+        .env("CENTORIA_EDITOR", "nano")
+        .output()
+        .unwrap();
+    assert_eq!(stdout(&output), format!("edit nano {} notes\n", config_dir));
+
+    let output = sandbox.cet(&["show", "--placeholders"]);
+    assert!(output.status.success());
+    assert!(
+        stdout(&output).contains("    {config_dir}        : directory of the configuration file\n")
+    );
+}
+
+#[test]
+fn exec_preserves_argument_boundaries() {
+    let sandbox = Sandbox::with_config(
+        "\
+[words]
+runas = 'function'
+command = \"printf '<%s>' {0..} {0..|join:,} {0..|each:-f %}\"
+",
+    );
+
+    let output = sandbox.cet(&["exec", "words", "--", "my notes.txt", "a.txt"]);
+    assert_eq!(
+        stdout(&output),
+        "<my notes.txt><a.txt><my notes.txt,a.txt><-f><my notes.txt><-f><a.txt>"
+    );
+}
+
+#[test]
+fn exec_script() {
+    let sandbox = Sandbox::with_config(
+        "\
+[count]
+runas = 'script'
+interpreter = 'bash'
+body = \'\'\'
+args=(\"$@\")
+echo \"${#args[@]}: ${args[1]}\"
+\'\'\'
+
+[greet]
+runas = 'script'
+script = 'scripts/greet.sh'
+",
+    );
+    fs::create_dir(sandbox.dir.path().join("scripts")).unwrap();
+    fs::write(
+        sandbox.dir.path().join("scripts/greet.sh"),
+        "#!/bin/sh\necho \"hello, $1\"\n",
+    )
+    .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let path = sandbox.dir.path().join("scripts/greet.sh");
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let output = sandbox.cet(&["exec", "count", "--", "a", "my notes.txt"]);
+    assert_eq!(stdout(&output), "2: my notes.txt\n");
+
+    let output = sandbox.cet(&["exec", "greet", "--", "centoria user"]);
+    assert_eq!(stdout(&output), "hello, centoria user\n");
+
+    let output = sandbox.cet(&["show", "greet"]);
+    assert!(stdout(&output)
+        .contains("Script         : scripts/greet.sh\nInterpreter    : (shebang of the script)\n"));
+}
+
+#[test]
+fn exec_in_working_directory() {
+    let sandbox = Sandbox::with_config(
+        "\
+[here]
+runas = 'alias'
+command = 'pwd'
+cwd = 'my dir'
+
+[home]
+runas = 'function'
+command = 'pwd {0?}'
+cwd = '~/my dir'
+
+[where]
+runas = 'subcommand'
+command = 'pwd'
+cwd = '${HOME}/my dir'
+[where.subcommands.physical]
+command = '-P'
+
+[missing]
+runas = 'alias'
+command = 'pwd'
+cwd = 'nowhere'
+",
+    );
+    fs::create_dir(sandbox.dir.path().join("my dir")).unwrap();
+
+    for args in [&["here"][..], &["home"], &["where", "--", "physical"]] {
+        let output = sandbox.cet(&[&["exec"][..], args].concat());
+        assert!(output.status.success());
+        assert!(stdout(&output).ends_with("/my dir\n"));
+    }
+
+    let output = sandbox.cet(&["exec", "missing"]);
+    assert_eq!(output.status.code(), Some(74));
+    assert!(stderr(&output).contains("nowhere does not exist"));
+}
+
+#[test]
+fn exec_never_prompts_without_terminal() {
+    let sandbox = Sandbox::with_config(
+        "\
+[greet]
+runas = 'function'
+command = 'echo Hello, {0}'
+prompt = true
+",
+    );
+
+    let output = sandbox.cet(&["exec", "greet", "--interactive"]);
+    assert_eq!(output.status.code(), Some(64));
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn list() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.cet(&["list"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "");
+
+    sandbox.cet(&["add", "search", "rg", "-d", "search text"]);
+    sandbox.cet(&["add", "fail", "false", "-c", "false"]);
+
+    let output = sandbox.cet(&["list"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "\
+fail   : No description provided [unavailable]
+search : search text
+"
+    );
+}
+
+#[test]
+fn show() {
+    let sandbox = Sandbox::new();
+    sandbox.cet(&["add", "search", "rg", "-d", "search text"]);
+
+    let output = sandbox.cet(&["show", "search"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "\
+Usage (Cet)    : cet exec search -- <EXTRA ARGS>
+Usage (Direct) : search <EXTRA ARGS>
+Execute        : rg
+Shell          : sh
+Status         : available
+
+search text
+"
+    );
+
+    let output = sandbox.cet(&["show", "unknown"]);
+    assert_eq!(output.status.code(), Some(127));
+}
+
+#[test]
+fn init() {
+    let sandbox = Sandbox::new();
+    sandbox.cet(&["add", "search", "rg"]);
+    sandbox.cet(&["add", "fail", "false", "-c", "false"]);
+
+    let output = sandbox.cet(&["init"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "\
+export CENTORIA_INIT={}
+# skipped unavailable functions (use `cet init --all` to export them):
+#   fail : condition `false` exited 1
+alias search='cet exec search -- '
+",
+            sandbox.config().display()
+        )
+    );
+
+    let output = sandbox.cet(&["init", "--all"]);
+    assert!(stdout(&output).contains("alias fail='cet exec fail -- '"));
+
+    // the path is quoted as a shell word
+    let path = sandbox.dir.path().join("it's.toml");
+    fs::write(&path, "").unwrap();
+    let output = sandbox
+        .command(&["init"])
+        .env("CENTORIA_CONFIG_PATH", &path)
+        .output()
+        .unwrap();
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "export CENTORIA_INIT='{}/it'\\''s.toml'\n",
+            sandbox.dir.path().display()
+        )
+    );
+}
+
+#[test]
+fn check() {
+    let sandbox = Sandbox::new();
+    sandbox.cet(&["add", "search", "rg"]);
+
+    let output = sandbox.cet(&["check"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "no problems found in 1 file(s)\n");
+
+    sandbox.cet(&["add", "logs", "tail {0}", "--shell", "ash"]);
+
+    let output = sandbox.cet(&["check"]);
+    assert_eq!(output.status.code(), Some(78));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "{}: logs.shell: not supported shell: ash\n",
+            sandbox.config().display()
+        )
+    );
+
+    // relative `cwd` is resolved from the directory of the configuration file
+    sandbox.cet(&["remove", "logs"]);
+    sandbox.cet(&["add", "build", "make", "--cwd", "src"]);
+    let output = sandbox.cet(&["check"]);
+    assert!(stdout(&output).contains("build.cwd: directory `src` does not exist\n"));
+
+    fs::create_dir(sandbox.dir.path().join("src")).unwrap();
+    let output = sandbox.cet(&["check"]);
+    assert!(output.status.success());
+
+    // placeholders inside quotes are warned, but not counted as problems
+    sandbox.cet(&["add", "greet", "echo \"hello, {0}\""]);
+    let output = sandbox.cet(&["check"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "{}: greet.command: warning: {{0}} is inside quotes and its value is substituted without quoting\n\
+             no problems found in 1 file(s)\n",
+            sandbox.config().display()
+        )
+    );
+
+    let output = sandbox.cet(&["exec", "greet", "--", "my name"]);
+    assert_eq!(stdout(&output), "hello, my name\n");
+}
+
+#[test]
+fn check_interpreters() {
+    let sandbox = Sandbox::with_config(
+        "\
+[hello]
+runas = 'function'
+command = 'print(\"hello, \" + {0})'
+shell = 'python3'
+condition = 'command -v python3'
+
+[greet]
+runas = 'function'
+command = 'echo {0}'
+shell = 'dash-e'
+
+[interpreters.dash-e]
+program = 'dash'
+flag = '-ec'
+",
+    );
+
+    let output = sandbox.cet(&["check"]);
+    assert_eq!(stdout(&output), "no problems found in 1 file(s)\n");
+
+    let output = sandbox.cet(&["exec", "hello", "--", "it's me"]);
+    assert_eq!(stdout(&output), "hello, it's me\n");
+
+    let output = sandbox.cet(&["exec", "greet", "--", "it's me"]);
+    assert_eq!(stdout(&output), "it's me\n");
+
+    // interpreters are kept when the configuration is saved
+    sandbox.cet(&["remove", "hello"]);
+    assert!(sandbox
+        .saved()
+        .ends_with("[interpreters.dash-e]\nprogram = 'dash'\nflag = '-ec'\n"));
+}
+
+#[test]
+fn doctor() {
+    let sandbox = Sandbox::new();
+    sandbox.cet(&["add", "fail", "false", "-c", "false"]);
+
+    let output = sandbox.cet(&["doctor"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains(&format!("{} : selected", sandbox.config().display())));
+    assert!(stdout(&output).contains("not loaded in this shell"));
+    assert!(stdout(&output).contains("fail : condition `false` exited 1"));
+}
+
+#[test]
+fn cache_clear() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.cet(&["cache", "clear"]);
+
+    assert!(output.status.success());
+}
+
+#[test]
+fn create_config_in_specified_path() {
+    let sandbox = Sandbox::new();
+    let path = sandbox
+        .dir
+        .path()
+        .join("nested")
+        .join("dir")
+        .join("config.toml");
+    let output = sandbox
+        .command(&["add", "search", "rg"])
+        .env("CENTORIA_CONFIG_PATH", &path)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(fs::read_to_string(&path).unwrap().contains("[search]"));
+    assert_eq!(sandbox.saved(), "");
+}
+
+#[test]
+fn config_flag() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.dir.path().join("flag.toml");
+    let path = path.to_str().unwrap();
+
+    assert!(sandbox
+        .cet(&["add", "search", "rg", "--config", path])
+        .status
+        .success());
+    assert!(sandbox
+        .cet(&["--config", path, "add", "ls", "ls"])
+        .status
+        .success());
+    assert_eq!(sandbox.saved(), "");
+
+    let output = sandbox.cet(&["--config", path, "list"]);
+    assert_eq!(
+        stdout(&output),
+        "\
+ls     : No description provided
+search : No description provided
+"
+    );
+}
+
+#[test]
+fn unwritable_config() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.config().join("config.toml"); // parent is a file
+    let output = sandbox
+        .command(&["add", "search", "rg"])
+        .env("CENTORIA_CONFIG_PATH", &path)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(74));
+    assert!(stderr(&output).contains(&format!(
+        "could not create directory {}",
+        sandbox.config().display()
+    )));
+}
@@ -0,0 +1,91 @@
+use std::path::Path;
+
+use centoria::{
+    Alias, Capture, ExecutionRequest, Executor, Function, Invocation, Recorder, SubCommand,
+};
+
+fn run(executor: &dyn Executor, args: &[&str]) -> Vec<Invocation> {
+    let args = args.iter().map(|s| s.to_string()).collect();
+    let mut recorder = Recorder::new(0);
+    let mut request = ExecutionRequest::new("test", args).backend(&mut recorder);
+    executor.execute(&mut request).unwrap();
+    drop(request);
+
+    recorder.invocations
+}
+
+fn invocation(shell: &str, command: &str) -> Invocation {
+    Invocation::new(shell, &["-c", command])
+}
+
+#[test]
+fn alias() {
+    let executor = Alias::new("rg", None, None, None, None);
+    assert_eq!(
+        run(&executor, &["Hello world", "./README.md"]),
+        vec![invocation("sh", "command rg 'Hello world' ./README.md")]
+    );
+
+    let executor = Alias::new("ls -al", None, Some("/tmp"), None, Some("fish"));
+    assert_eq!(
+        run(&executor, &[]),
+        vec![invocation("fish", "command ls -al").current_dir(Some(Path::new("/tmp")))]
+    );
+}
+
+#[test]
+fn function() {
+    let executor = Function::new("tail -f /var/log/{0}/error.log", None, None, None, None);
+    assert_eq!(
+        run(&executor, &["nginx"]),
+        vec![invocation("sh", "command tail -f /var/log/nginx/error.log")]
+    );
+
+    let executor = Function::new("cp {1..} {0}", None, None, None, Some("bash"));
+    assert_eq!(
+        run(&executor, &["/dest", "a", "b"]),
+        vec![invocation("bash", "command cp a b /dest")]
+    );
+}
+
+#[test]
+fn subcommand() {
+    let mut executor = SubCommand::new("docker", "c", "container", None, Some("/tmp"), None, None);
+    executor
+        .add(SubCommand::new(
+            "docker",
+            "prune",
+            "{0} prune",
+            None,
+            None,
+            None,
+            None,
+        ))
+        .unwrap();
+
+    let invocation = |command| invocation("sh", command).current_dir(Some(Path::new("/tmp")));
+    assert_eq!(
+        run(&executor, &["c", "ls"]),
+        vec![invocation("command docker container ls")]
+    );
+    assert_eq!(
+        run(&executor, &["prune", "image"]),
+        vec![invocation("command docker image prune")]
+    );
+    assert_eq!(
+        run(&executor, &["ps"]),
+        vec![invocation("command docker ps")]
+    );
+}
+
+#[test]
+fn capture() {
+    let executor = Function::new("echo {0}; exit 3", None, None, None, None);
+    let mut capture = Capture::new();
+    let mut request = ExecutionRequest::new("test", vec!["Hello".to_owned()]).backend(&mut capture);
+    let status = executor.execute(&mut request).unwrap();
+    drop(request);
+
+    assert_eq!(status.code(), Some(3));
+    assert_eq!(capture.last().unwrap().stdout, b"Hello\n");
+}